@@ -0,0 +1,13 @@
+fn main() {
+    #[cfg(feature = "capi")]
+    {
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+
+        println!("cargo:rerun-if-changed=src/capi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+
+        cbindgen::generate(&dir)
+            .expect("error generating C header")
+            .write_to_file(format!("{dir}/include/nonogram.h"));
+    }
+}
@@ -0,0 +1,46 @@
+// times re-solving a puzzle after a clue edit in a live session against
+// solving the edited puzzle from scratch
+//
+//     cargo run --release --example session -- nonograms/cat
+
+use std::{env, fs, time::Instant};
+
+use nonogram::{common, logic::Line, session::Session};
+
+fn main() {
+    let path = env::args().nth(1).unwrap_or("nonograms/cat".to_string());
+    let input = fs::read_to_string(&path).expect("puzzle file is readable");
+    let (rows, cols) = common::parse_checked(&input).expect("puzzle is valid");
+
+    let start = Instant::now();
+    let mut session = Session::new(rows.clone(), cols.clone());
+    let solution = session.solve();
+    println!("{:<24} {:>12.2?}", "first solve", start.elapsed());
+
+    let Some(solution) = solution else {
+        println!("puzzle is unsolvable");
+        return;
+    };
+
+    // flip one cell of the solution so the edited puzzle is still solvable
+    let (row, col) = (rows.len() / 2, cols.len() / 2);
+    let mut grid = solution;
+    grid[row][col] = !grid[row][col];
+    let (new_rows, new_cols) = common::clues(&grid);
+
+    let start = Instant::now();
+    session
+        .set_clue(Line::Row(row), new_rows[row].clone())
+        .unwrap();
+    session
+        .set_clue(Line::Col(col), new_cols[col].clone())
+        .unwrap();
+    let edited = session.solve();
+    println!("{:<24} {:>12.2?}", "re-solve after edit", start.elapsed());
+
+    let start = Instant::now();
+    let fresh = Session::new(new_rows, new_cols).solve();
+    println!("{:<24} {:>12.2?}", "edited from scratch", start.elapsed());
+
+    assert_eq!(edited.is_some(), fresh.is_some());
+}
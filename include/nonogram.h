@@ -0,0 +1,102 @@
+#ifndef NONOGRAM_H
+#define NONOGRAM_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result of every fallible call. Anything but `NONOGRAM_ERROR_OK` leaves the out
+// parameters untouched.
+typedef enum NonogramError {
+  NONOGRAM_ERROR_OK = 0,
+  NONOGRAM_ERROR_NULL_POINTER = 1,
+  NONOGRAM_ERROR_INVALID_PUZZLE = 2,
+  NONOGRAM_ERROR_UNKNOWN_SOLVER = 3,
+  NONOGRAM_ERROR_UNSOLVABLE = 4,
+  NONOGRAM_ERROR_PANIC = 5,
+} NonogramError;
+
+// Opaque puzzle, created with `nonogram_puzzle_new`.
+typedef struct NonogramPuzzle NonogramPuzzle;
+
+// Opaque solved grid, created with `nonogram_solve`.
+typedef struct NonogramSolution NonogramSolution;
+
+typedef struct NonogramStats {
+  double solve_ms;
+} NonogramStats;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a puzzle from its clues. The numbers of all row clues are laid out
+// one after another in `row_clues`, and `row_lens[i]` is the number of blocks
+// in row `i`; the same goes for columns. Free with `nonogram_puzzle_free`.
+//
+// # Safety
+//
+// The arrays must hold as many elements as the lengths say, and `out` must
+// be a valid pointer.
+enum NonogramError nonogram_puzzle_new(const uint32_t *row_clues,
+                                       const uintptr_t *row_lens,
+                                       uintptr_t num_rows,
+                                       const uint32_t *col_clues,
+                                       const uintptr_t *col_lens,
+                                       uintptr_t num_cols,
+                                       struct NonogramPuzzle **out);
+
+// # Safety
+//
+// `puzzle` must come from `nonogram_puzzle_new` and not be freed already.
+void nonogram_puzzle_free(struct NonogramPuzzle *puzzle);
+
+// Solves a puzzle with the named backend, or the default one when `solver`
+// is null. Free the solution with `nonogram_solution_free`.
+//
+// # Safety
+//
+// `puzzle` must be a live puzzle, `solver` null or a nul-terminated string,
+// and `out` a valid pointer.
+enum NonogramError nonogram_solve(const struct NonogramPuzzle *puzzle,
+                                  const char *solver,
+                                  struct NonogramSolution **out);
+
+// # Safety
+//
+// `solution` must be a live solution.
+uintptr_t nonogram_solution_width(const struct NonogramSolution *solution);
+
+// # Safety
+//
+// `solution` must be a live solution.
+uintptr_t nonogram_solution_height(const struct NonogramSolution *solution);
+
+// Cells row by row, 1 for filled and 0 for empty. The buffer holds
+// width * height bytes and lives as long as the solution.
+//
+// # Safety
+//
+// `solution` must be a live solution.
+const uint8_t *nonogram_solution_cells(const struct NonogramSolution *solution);
+
+// # Safety
+//
+// `solution` must be a live solution and `out` a valid pointer.
+enum NonogramError nonogram_solution_stats(const struct NonogramSolution *solution,
+                                           struct NonogramStats *out);
+
+// # Safety
+//
+// `solution` must come from `nonogram_solve` and not be freed already.
+void nonogram_solution_free(struct NonogramSolution *solution);
+
+// Static, nul-terminated description of an error code.
+const char *nonogram_error_message(enum NonogramError error);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* NONOGRAM_H */
@@ -0,0 +1,64 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    explain::Cell,
+    logic::{format_partial, Partial},
+    session::Session,
+    Vec2D,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ambiguity {
+    // the cells every solution agrees on, None where solutions differ
+    pub grid: Partial,
+    pub undetermined: usize,
+    // cells to add as givens so that exactly one solution is left
+    pub givens: Vec<Cell>,
+}
+
+pub fn ambiguity(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Ambiguity> {
+    let mut session = Session::new(rows, cols);
+
+    let grid = session.backbone()?;
+    let undetermined = grid.iter().flatten().filter(|c| c.is_none()).count();
+
+    let givens = if undetermined == 0 {
+        Vec::new()
+    } else {
+        session.givens()?
+    };
+
+    Some(Ambiguity {
+        grid,
+        undetermined,
+        givens,
+    })
+}
+
+impl Ambiguity {
+    pub fn is_unique(&self) -> bool {
+        self.undetermined == 0
+    }
+}
+
+impl fmt::Display for Ambiguity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GRID:\n{}", format_partial(&self.grid))?;
+        writeln!(f, "UNDETERMINED:\n{}", self.undetermined)?;
+
+        if self.givens.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "GIVENS:")?;
+
+        for cell in &self.givens {
+            let value = if cell.filled { "filled" } else { "empty" };
+            writeln!(f, "- ({}, {}) {value}", cell.row + 1, cell.col + 1)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+use crate::{logic::Partial, session::Session, Vec2D};
+
+// the cells that take the same value in every solution, None where solutions
+// differ; None if the puzzle has no solution at all
+pub fn backbone(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Partial> {
+    Session::new(rows, cols).backbone()
+}
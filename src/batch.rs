@@ -0,0 +1,317 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{self, Puzzle},
+    report::Status,
+    solvers::{self, Solve},
+    Vec2D,
+};
+
+type Clues = (Vec2D<u32>, Vec2D<u32>);
+
+pub struct Job {
+    pub name: String,
+    pub puzzle: Result<Clues, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub puzzle: String,
+    pub solver: String,
+    pub status: Status,
+    pub width: usize,
+    pub height: usize,
+    pub solve_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Summary {
+    pub sat: usize,
+    pub unsat: usize,
+    pub timeout: usize,
+    pub invalid: usize,
+    pub solve_ms: f64,
+    pub elapsed: Duration,
+}
+
+// splits concatenated puzzles, either in the text format with optional blank
+// lines between them or as a stream of JSON objects
+pub fn split(input: &str) -> Vec<Result<Clues, String>> {
+    if input.trim_start().starts_with('{') {
+        return serde_json::Deserializer::from_str(input)
+            .into_iter::<Puzzle>()
+            .map(|puzzle| {
+                let puzzle = puzzle.map_err(|e| format!("error parsing nonogram: {e}"))?;
+                checked(puzzle.rows, puzzle.cols)
+            })
+            .collect();
+    }
+
+    let mut puzzles = Vec::new();
+    let mut lines = input.lines().enumerate().peekable();
+
+    loop {
+        while lines.next_if(|(_, line)| line.trim().is_empty()).is_some() {}
+
+        let Some((number, header)) = lines.next() else {
+            break;
+        };
+
+        let Some((num_rows, num_cols)) = common::parse_header(header) else {
+            // there is no telling where the next puzzle starts
+            puzzles.push(Err(format!(
+                "line {}: expected a `rows cols` header",
+                number + 1
+            )));
+            break;
+        };
+
+        let body: Vec<_> = lines
+            .by_ref()
+            .take(num_rows + num_cols)
+            .map(|(_, line)| line)
+            .collect();
+
+        let text = std::iter::once(header).chain(body).collect::<Vec<_>>();
+
+        let puzzle = common::parse(text.join("\n"))
+            .ok_or_else(|| format!("line {}: error parsing nonogram", number + 1))
+            .and_then(|(rows, cols)| checked(rows, cols));
+
+        puzzles.push(puzzle);
+    }
+
+    puzzles
+}
+
+fn checked(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Result<Clues, String> {
+    common::validate(&rows, &cols).map_err(|e| format!("incorrect nonogram: {e}"))?;
+    Ok((rows, cols))
+}
+
+// one job per puzzle, named `name` or `name:N` when the input holds several
+pub fn jobs(name: &str, input: &str) -> Vec<Job> {
+    let puzzles = split(input);
+    let many = puzzles.len() > 1;
+
+    puzzles
+        .into_iter()
+        .enumerate()
+        .map(|(i, puzzle)| Job {
+            name: if many {
+                format!("{name}:{}", i + 1)
+            } else {
+                name.to_string()
+            },
+            puzzle,
+        })
+        .collect()
+}
+
+// files and the files directly inside directories, in name order
+pub fn load(paths: &[PathBuf]) -> Vec<Job> {
+    let mut jobs = Vec::new();
+
+    for path in paths {
+        if !path.is_dir() {
+            jobs.extend(load_file(path));
+            continue;
+        }
+
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                jobs.push(Job {
+                    name: path.display().to_string(),
+                    puzzle: Err(format!("error reading {}: {e}", path.display())),
+                });
+                continue;
+            }
+        };
+
+        let mut files: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file())
+            .filter(|path| {
+                !path
+                    .file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+            })
+            .collect();
+
+        files.sort();
+
+        for file in files {
+            jobs.extend(load_file(&file));
+        }
+    }
+
+    jobs
+}
+
+fn load_file(path: &Path) -> Vec<Job> {
+    let name = path.display().to_string();
+
+    match fs::read_to_string(path) {
+        Ok(input) => jobs(&name, &input),
+        Err(e) => vec![Job {
+            puzzle: Err(format!("error reading {name}: {e}")),
+            name,
+        }],
+    }
+}
+
+// solves the jobs on `workers` threads and hands each record to `each` as
+// soon as it is done, so records come in completion order. A solver that
+// times out is cancelled if it can be; the others cannot be interrupted, so
+// they are left to finish in the background and their worker moves on
+pub fn run(
+    jobs: Vec<Job>,
+    solver: &str,
+    solve: Solve,
+    workers: usize,
+    timeout: Option<Duration>,
+    mut each: impl FnMut(&Record),
+) -> Summary {
+    let start = Instant::now();
+    let queue = Mutex::new(jobs.into_iter());
+    let (tx, rx) = mpsc::channel();
+
+    let mut summary = Summary::default();
+
+    thread::scope(|scope| {
+        for _ in 0..workers.max(1) {
+            let tx = tx.clone();
+            let queue = &queue;
+
+            scope.spawn(move || loop {
+                let Some(job) = queue.lock().unwrap().next() else {
+                    break;
+                };
+
+                if tx.send(solve_job(job, solver, solve, timeout)).is_err() {
+                    break;
+                }
+            });
+        }
+
+        drop(tx);
+
+        for record in rx {
+            summary.add(&record);
+            each(&record);
+        }
+    });
+
+    summary.elapsed = start.elapsed();
+    summary
+}
+
+fn solve_job(job: Job, solver: &str, solve: Solve, timeout: Option<Duration>) -> Record {
+    let mut record = Record {
+        puzzle: job.name,
+        solver: solver.to_string(),
+        status: Status::Invalid,
+        width: 0,
+        height: 0,
+        solve_ms: 0.0,
+        error: None,
+    };
+
+    let (rows, cols) = match job.puzzle {
+        Ok(clues) => clues,
+        Err(e) => {
+            record.error = Some(e);
+            return record;
+        }
+    };
+
+    record.width = cols.len();
+    record.height = rows.len();
+
+    let start = Instant::now();
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let cancellable = solvers::get_cancellable(solver);
+
+    let (tx, rx) = mpsc::channel();
+    let flag = cancel.clone();
+    thread::spawn(move || {
+        let result = match cancellable {
+            Some(solve) => solve(rows, cols, &flag),
+            None => solve(rows, cols),
+        };
+
+        let _ = tx.send(result);
+    });
+
+    let result = match timeout {
+        Some(timeout) => rx.recv_timeout(timeout),
+        None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+
+    record.solve_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    record.status = match result {
+        Ok(Some(_)) => Status::Sat,
+        Ok(None) => Status::Unsat,
+        Err(RecvTimeoutError::Timeout) => {
+            cancel.store(true, Ordering::Relaxed);
+            Status::Timeout
+        }
+        Err(RecvTimeoutError::Disconnected) => {
+            record.error = Some("solver crashed".to_string());
+            Status::Invalid
+        }
+    };
+
+    record
+}
+
+impl Summary {
+    fn add(&mut self, record: &Record) {
+        match record.status {
+            Status::Sat => self.sat += 1,
+            Status::Unsat => self.unsat += 1,
+            Status::Timeout => self.timeout += 1,
+            Status::Invalid => self.invalid += 1,
+        }
+
+        self.solve_ms += record.solve_ms;
+    }
+
+    pub fn total(&self) -> usize {
+        self.sat + self.unsat + self.timeout + self.invalid
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<10} {:>8}", "STATUS", "COUNT")?;
+        writeln!(f, "{:<10} {:>8}", "sat", self.sat)?;
+        writeln!(f, "{:<10} {:>8}", "unsat", self.unsat)?;
+        writeln!(f, "{:<10} {:>8}", "timeout", self.timeout)?;
+        writeln!(f, "{:<10} {:>8}", "invalid", self.invalid)?;
+        writeln!(f, "{:<10} {:>8}", "total", self.total())?;
+        writeln!(
+            f,
+            "SOLVE TIME:\n{:.2?}",
+            Duration::from_secs_f64(self.solve_ms / 1000.0)
+        )?;
+        writeln!(f, "WALL TIME:\n{:.2?}", self.elapsed)
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    ffi::{c_char, CStr},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr, slice,
+    time::Instant,
+};
+
+use crate::{common::validate, report::Stats, solvers, Vec2D};
+
+/// Result of every fallible call. Anything but `NONOGRAM_ERROR_OK` leaves the out
+/// parameters untouched.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonogramError {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidPuzzle = 2,
+    UnknownSolver = 3,
+    Unsolvable = 4,
+    Panic = 5,
+}
+
+/// Opaque puzzle, created with `nonogram_puzzle_new`.
+pub struct NonogramPuzzle {
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+}
+
+/// Opaque solved grid, created with `nonogram_solve`.
+pub struct NonogramSolution {
+    width: usize,
+    height: usize,
+    cells: Vec<u8>,
+    stats: NonogramStats,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NonogramStats {
+    pub solve_ms: f64,
+}
+
+fn guard(f: impl FnOnce() -> NonogramError) -> NonogramError {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(NonogramError::Panic)
+}
+
+unsafe fn clues(nums: *const u32, lens: *const usize, count: usize) -> Option<Vec2D<u32>> {
+    if count == 0 {
+        return Some(Vec::new());
+    }
+
+    if lens.is_null() {
+        return None;
+    }
+
+    let lens = slice::from_raw_parts(lens, count);
+    let total = lens.iter().sum();
+
+    if total > 0 && nums.is_null() {
+        return None;
+    }
+
+    let nums = if total > 0 {
+        slice::from_raw_parts(nums, total)
+    } else {
+        &[]
+    };
+
+    let mut offset = 0;
+
+    let lines = lens
+        .iter()
+        .map(|&len| {
+            let line = nums[offset..offset + len].to_vec();
+            offset += len;
+            line
+        })
+        .collect();
+
+    Some(lines)
+}
+
+/// Creates a puzzle from its clues. The numbers of all row clues are laid out
+/// one after another in `row_clues`, and `row_lens[i]` is the number of blocks
+/// in row `i`; the same goes for columns. Free with `nonogram_puzzle_free`.
+///
+/// # Safety
+///
+/// The arrays must hold as many elements as the lengths say, and `out` must
+/// be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn nonogram_puzzle_new(
+    row_clues: *const u32,
+    row_lens: *const usize,
+    num_rows: usize,
+    col_clues: *const u32,
+    col_lens: *const usize,
+    num_cols: usize,
+    out: *mut *mut NonogramPuzzle,
+) -> NonogramError {
+    guard(|| {
+        if out.is_null() {
+            return NonogramError::NullPointer;
+        }
+
+        let (Some(rows), Some(cols)) = (
+            clues(row_clues, row_lens, num_rows),
+            clues(col_clues, col_lens, num_cols),
+        ) else {
+            return NonogramError::NullPointer;
+        };
+
+        if validate(&rows, &cols).is_err() {
+            return NonogramError::InvalidPuzzle;
+        }
+
+        *out = Box::into_raw(Box::new(NonogramPuzzle { rows, cols }));
+
+        NonogramError::Ok
+    })
+}
+
+/// # Safety
+///
+/// `puzzle` must come from `nonogram_puzzle_new` and not be freed already.
+#[no_mangle]
+pub unsafe extern "C" fn nonogram_puzzle_free(puzzle: *mut NonogramPuzzle) {
+    if !puzzle.is_null() {
+        drop(Box::from_raw(puzzle));
+    }
+}
+
+/// Solves a puzzle with the named backend, or the default one when `solver`
+/// is null. Free the solution with `nonogram_solution_free`.
+///
+/// # Safety
+///
+/// `puzzle` must be a live puzzle, `solver` null or a nul-terminated string,
+/// and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn nonogram_solve(
+    puzzle: *const NonogramPuzzle,
+    solver: *const c_char,
+    out: *mut *mut NonogramSolution,
+) -> NonogramError {
+    guard(|| {
+        if puzzle.is_null() || out.is_null() {
+            return NonogramError::NullPointer;
+        }
+
+        let name = if solver.is_null() {
+            solvers::DEFAULT
+        } else {
+            match CStr::from_ptr(solver).to_str() {
+                Ok(name) => name,
+                Err(_) => return NonogramError::UnknownSolver,
+            }
+        };
+
+        let Some(solve) = solvers::get(name) else {
+            return NonogramError::UnknownSolver;
+        };
+
+        let puzzle = &*puzzle;
+
+        let start = Instant::now();
+        let solution = solve(puzzle.rows.clone(), puzzle.cols.clone());
+        let stats = Stats::new(Default::default(), start.elapsed());
+
+        let Some(solution) = solution else {
+            return NonogramError::Unsolvable;
+        };
+
+        *out = Box::into_raw(Box::new(NonogramSolution {
+            width: puzzle.cols.len(),
+            height: puzzle.rows.len(),
+            cells: solution.into_iter().flatten().map(u8::from).collect(),
+            stats: NonogramStats {
+                solve_ms: stats.solve_ms,
+            },
+        }));
+
+        NonogramError::Ok
+    })
+}
+
+/// # Safety
+///
+/// `solution` must be a live solution.
+#[no_mangle]
+pub unsafe extern "C" fn nonogram_solution_width(solution: *const NonogramSolution) -> usize {
+    solution.as_ref().map_or(0, |s| s.width)
+}
+
+/// # Safety
+///
+/// `solution` must be a live solution.
+#[no_mangle]
+pub unsafe extern "C" fn nonogram_solution_height(solution: *const NonogramSolution) -> usize {
+    solution.as_ref().map_or(0, |s| s.height)
+}
+
+/// Cells row by row, 1 for filled and 0 for empty. The buffer holds
+/// width * height bytes and lives as long as the solution.
+///
+/// # Safety
+///
+/// `solution` must be a live solution.
+#[no_mangle]
+pub unsafe extern "C" fn nonogram_solution_cells(solution: *const NonogramSolution) -> *const u8 {
+    solution.as_ref().map_or(ptr::null(), |s| s.cells.as_ptr())
+}
+
+/// # Safety
+///
+/// `solution` must be a live solution and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn nonogram_solution_stats(
+    solution: *const NonogramSolution,
+    out: *mut NonogramStats,
+) -> NonogramError {
+    match (solution.as_ref(), out.as_mut()) {
+        (Some(solution), Some(out)) => {
+            *out = solution.stats;
+            NonogramError::Ok
+        }
+        _ => NonogramError::NullPointer,
+    }
+}
+
+/// # Safety
+///
+/// `solution` must come from `nonogram_solve` and not be freed already.
+#[no_mangle]
+pub unsafe extern "C" fn nonogram_solution_free(solution: *mut NonogramSolution) {
+    if !solution.is_null() {
+        drop(Box::from_raw(solution));
+    }
+}
+
+/// Static, nul-terminated description of an error code.
+#[no_mangle]
+pub extern "C" fn nonogram_error_message(error: NonogramError) -> *const c_char {
+    let message: &'static CStr = match error {
+        NonogramError::Ok => c"ok",
+        NonogramError::NullPointer => c"null or malformed pointer argument",
+        NonogramError::InvalidPuzzle => c"incorrect nonogram",
+        NonogramError::UnknownSolver => c"wrong solver name",
+        NonogramError::Unsolvable => c"unsolvable",
+        NonogramError::Panic => c"internal error",
+    };
+
+    message.as_ptr()
+}
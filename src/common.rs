@@ -1,5 +1,15 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
 use crate::Vec2D;
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Puzzle {
+    pub rows: Vec2D<u32>,
+    pub cols: Vec2D<u32>,
+}
+
 pub(crate) fn find_solutions(cons: &[u32], len: u32) -> Vec2D<bool> {
     let curr = match cons.first() {
         Some(&n) => n,
@@ -15,15 +25,8 @@ pub(crate) fn find_solutions(cons: &[u32], len: u32) -> Vec2D<bool> {
     let mut results = Vec::new();
 
     for start in 0..=(len - curr) {
-        let mut part = Vec::new();
-
-        for _ in 0..start {
-            part.push(false);
-        }
-
-        for _ in 0..curr {
-            part.push(true);
-        }
+        let mut part = vec![false; start as usize];
+        part.extend(std::iter::repeat_n(true, curr as usize));
 
         if len - (start + curr) == 0 && cons.len() == 1 {
             results.push(part);
@@ -41,68 +44,251 @@ pub(crate) fn find_solutions(cons: &[u32], len: u32) -> Vec2D<bool> {
     results
 }
 
+pub(crate) fn fits(placement: &[bool], known: &[Option<bool>]) -> bool {
+    std::iter::zip(placement, known).all(|(&p, k)| k.is_none_or(|k| k == p))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    // 1-based, if the error can be pinned to a line
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    fn at(line: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            line: Some(line),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
 pub fn parse(input: String) -> Option<(Vec2D<u32>, Vec2D<u32>)> {
-    let mut lines = input.lines();
+    parse_text(&input).ok()
+}
 
-    let (num_rows, num_cols) = lines
-        .next()
-        .and_then(|line| line.split_once(' '))
-        .and_then(|(rows, cols)| Option::zip(rows.parse().ok(), cols.parse().ok()))?;
-
-    let mut collect = |num| {
-        (0..num).try_fold(Vec::new(), |mut acc, _| {
-            let line = lines.next()?;
-            let nums = line.split_whitespace().try_fold(Vec::new(), |mut acc, n| {
-                let n = n.parse().ok()?;
-                acc.push(n);
-                Some(acc)
-            })?;
-
-            acc.push(nums);
-            Some(acc)
-        })
-    };
+pub fn parse_json(input: String) -> Option<(Vec2D<u32>, Vec2D<u32>)> {
+    let puzzle: Puzzle = serde_json::from_str(&input).ok()?;
+    Some((puzzle.rows, puzzle.cols))
+}
 
-    Some((collect(num_rows)?, collect(num_cols)?))
+// the `rows cols` line a text puzzle starts with
+pub(crate) fn parse_header(header: &str) -> Option<(usize, usize)> {
+    let size: Vec<usize> = header
+        .split_whitespace()
+        .map(|n| n.parse().ok())
+        .collect::<Option<_>>()?;
+
+    match size[..] {
+        [rows, cols] => Some((rows, cols)),
+        _ => None,
+    }
 }
 
-pub fn display(solution: &Vec2D<bool>) {
-    print!("╔");
+fn parse_text(input: &str) -> Result<(Vec2D<u32>, Vec2D<u32>), ParseError> {
+    let mut lines = input.lines();
+
+    let header = lines.next().unwrap_or_default();
+    let (num_rows, num_cols) = parse_header(header)
+        .ok_or_else(|| ParseError::at(1, format!("expected `rows cols`, found `{header}`")))?;
+
+    let mut clues = Vec::new();
+
+    for i in 0..num_rows + num_cols {
+        let number = i + 2;
 
-    for _ in 0..solution[0].len() {
-        print!("═");
+        let Some(line) = lines.next() else {
+            return Err(ParseError::at(
+                number,
+                format!(
+                    "expected {} clue lines after the header, found {i}",
+                    num_rows + num_cols
+                ),
+            ));
+        };
+
+        let nums = line
+            .split_whitespace()
+            .map(|n| {
+                n.parse()
+                    .map_err(|_| ParseError::at(number, format!("expected a number, found `{n}`")))
+            })
+            .collect::<Result<Vec<u32>, _>>()?;
+
+        clues.push(nums);
     }
 
-    print!("╗\n");
+    let cols = clues.split_off(num_rows);
 
-    for row in solution {
-        print!("║");
+    Ok((clues, cols))
+}
+
+// like `parse` followed by `validate`, but errors point at the offending line
+pub fn parse_checked(input: &str) -> Result<(Vec2D<u32>, Vec2D<u32>), ParseError> {
+    let (rows, cols) = parse_text(input)?;
+
+    // clue lines follow the header, rows first
+    check_clues(&rows, &cols, |i| Some(i + 2))?;
 
-        for &cell in row {
-            print!("{}", if cell { '#' } else { '.' });
+    Ok((rows, cols))
+}
+
+pub fn parse_json_checked(input: &str) -> Result<(Vec2D<u32>, Vec2D<u32>), ParseError> {
+    let puzzle: Puzzle = serde_json::from_str(input).map_err(|e| ParseError {
+        line: Some(e.line()).filter(|&line| line > 0),
+        message: e.to_string(),
+    })?;
+
+    check_clues(&puzzle.rows, &puzzle.cols, |_| None)?;
+
+    Ok((puzzle.rows, puzzle.cols))
+}
+
+fn check_clues(
+    rows: &Vec2D<u32>,
+    cols: &Vec2D<u32>,
+    line: impl Fn(usize) -> Option<usize>,
+) -> Result<(), ParseError> {
+    let lines = rows
+        .iter()
+        .enumerate()
+        .map(|(i, clue)| ("row", i, clue, cols.len(), "wide"))
+        .chain(
+            cols.iter()
+                .enumerate()
+                .map(|(i, clue)| ("column", i, clue, rows.len(), "tall")),
+        );
+
+    for (n, (kind, i, clue, len, dimension)) in lines.enumerate() {
+        let error = |message| ParseError {
+            line: line(n),
+            message,
+        };
+
+        if clue.contains(&0) {
+            return Err(error(format!("{kind} {} has a zero in its clue", i + 1)));
+        }
+
+        let needed = needed(clue);
+
+        if needed > len as u64 {
+            return Err(error(format!(
+                "{kind} {} needs {needed} cells but the puzzle is {len} {dimension}",
+                i + 1
+            )));
         }
+    }
 
-        print!("║\n");
+    Ok(())
+}
+
+// the fewest cells a line with this clue fits in, summed wide enough that
+// no clue of u32s can overflow it
+fn needed(clue: &[u32]) -> u64 {
+    let blocks: u64 = clue.iter().map(|&n| u64::from(n)).sum();
+    (blocks + clue.len() as u64).saturating_sub(1)
+}
+
+pub fn serialize(rows: &Vec2D<u32>, cols: &Vec2D<u32>) -> String {
+    let mut out = format!("{} {}\n", rows.len(), cols.len());
+
+    for line in rows.iter().chain(cols) {
+        let nums: Vec<_> = line.iter().map(u32::to_string).collect();
+        out.push_str(&nums.join(" "));
+        out.push('\n');
     }
 
-    print!("╚");
+    out
+}
+
+pub fn serialize_json(rows: &Vec2D<u32>, cols: &Vec2D<u32>) -> String {
+    let puzzle = Puzzle {
+        rows: rows.clone(),
+        cols: cols.clone(),
+    };
+
+    serde_json::to_string(&puzzle).expect("puzzle is always serializable")
+}
+
+pub fn clues(grid: &Vec2D<bool>) -> (Vec2D<u32>, Vec2D<u32>) {
+    let runs = |cells: &mut dyn Iterator<Item = bool>| {
+        let mut runs = Vec::new();
+        let mut curr = 0;
+
+        for cell in cells {
+            if cell {
+                curr += 1;
+            } else if curr > 0 {
+                runs.push(curr);
+                curr = 0;
+            }
+        }
+
+        if curr > 0 {
+            runs.push(curr);
+        }
+
+        runs
+    };
+
+    let width = grid.first().map_or(0, Vec::len);
+
+    let rows = grid
+        .iter()
+        .map(|row| runs(&mut row.iter().copied()))
+        .collect();
+    let cols = (0..width)
+        .map(|col| runs(&mut grid.iter().map(|row| row[col])))
+        .collect();
+
+    (rows, cols)
+}
+
+pub fn display(solution: &Vec2D<bool>) {
+    print!("{}", format_grid(solution));
+}
+
+pub fn format_grid(solution: &Vec2D<bool>) -> String {
+    let width = solution.first().map_or(0, Vec::len);
+
+    let mut out = String::new();
+
+    out.push('╔');
+    out.extend(std::iter::repeat_n('═', width));
+    out.push_str("╗\n");
 
-    for _ in 0..solution[0].len() {
-        print!("═");
+    for row in solution {
+        out.push('║');
+        out.extend(row.iter().map(|&cell| if cell { '#' } else { '.' }));
+        out.push_str("║\n");
     }
 
-    print!("╝");
-    print!("\n");
+    out.push('╚');
+    out.extend(std::iter::repeat_n('═', width));
+    out.push_str("╝\n");
+
+    out
+}
+
+// whether a single line of `len` cells can hold this clue
+pub fn clue_fits(clue: &[u32], len: usize) -> bool {
+    !clue.contains(&0) && needed(clue) <= len as u64
 }
 
 pub fn validate(rows: &Vec2D<u32>, cols: &Vec2D<u32>) -> Result<(), String> {
     let validate = |constraints: &Vec2D<u32>, len: usize| {
         for (i, cs) in constraints.iter().enumerate() {
-            if cs.iter().sum::<u32>() + cs.len() as u32 - 1 > len as u32 {
-                return Err(format!("in position {}", i + 1));
-            }
-
-            if cs.contains(&0) {
+            if !clue_fits(cs, len) {
                 return Err(format!("in position {}", i + 1));
             }
         }
@@ -118,3 +304,45 @@ pub fn validate(rows: &Vec2D<u32>, cols: &Vec2D<u32>) -> Result<(), String> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(input: &str) -> Option<usize> {
+        parse_checked(input).unwrap_err().line
+    }
+
+    #[test]
+    fn parse_checked_accepts_valid_puzzle() {
+        let (rows, cols) = parse_checked("2 2\n2\n1\n1\n2\n").unwrap();
+
+        assert_eq!(rows, vec![vec![2], vec![1]]);
+        assert_eq!(cols, vec![vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn parse_checked_points_at_bad_line() {
+        assert_eq!(line("2 x\n"), Some(1));
+        assert_eq!(line("2 2\n1\n1 a\n1\n1\n"), Some(3));
+        assert_eq!(line("2 2\n1\n1\n1\n"), Some(5));
+    }
+
+    #[test]
+    fn parse_checked_points_at_bad_clue() {
+        // rows come first, then columns
+        assert_eq!(line("2 2\n1\n0\n1\n1\n"), Some(3));
+        assert_eq!(line("2 2\n1\n1\n1\n1 1\n"), Some(5));
+    }
+
+    #[test]
+    fn huge_clues_do_not_overflow() {
+        let err = parse_checked("1 1\n4294967295 1\n1\n").unwrap_err();
+        assert_eq!(err.line, Some(2));
+
+        assert!(parse_json_checked(r#"{"rows":[[4294967295,1]],"cols":[[1]]}"#).is_err());
+        assert!(validate(&vec![vec![u32::MAX, 1]], &vec![vec![1]]).is_err());
+        assert!(validate(&vec![vec![1]], &vec![vec![u32::MAX; 3]]).is_err());
+        assert!(!clue_fits(&[u32::MAX, u32::MAX], u32::MAX as usize));
+    }
+}
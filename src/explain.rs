@@ -0,0 +1,134 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    logic::{is_solved, Line, Logic, Step, Tier},
+    Vec2D,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    Overlap,
+    NoPlacement,
+    Contradiction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cell {
+    pub row: usize,
+    pub col: usize,
+    pub filled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deduction {
+    pub line: Line,
+    pub clue: Vec<u32>,
+    pub cells: Vec<Cell>,
+    pub reason: Reason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Explanation {
+    pub deductions: Vec<Deduction>,
+    pub solved: bool,
+}
+
+impl Deduction {
+    pub(crate) fn new(logic: &Logic, step: Step) -> Self {
+        Deduction {
+            line: step.line,
+            clue: logic.clue(step.line).to_vec(),
+            cells: step
+                .cells
+                .into_iter()
+                .map(|(row, col, filled)| Cell { row, col, filled })
+                .collect(),
+            reason: match step.tier {
+                Tier::Overlap => Reason::Overlap,
+                Tier::Line => Reason::NoPlacement,
+                Tier::Probe | Tier::Backtrack => Reason::Contradiction,
+            },
+        }
+    }
+}
+
+pub fn explain(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Explanation> {
+    let logic = Logic::new(rows, cols);
+
+    let mut grid = logic.empty();
+    let mut steps = Vec::new();
+
+    logic.deduce(&mut grid, &mut steps).ok()?;
+
+    Some(Explanation {
+        deductions: steps
+            .into_iter()
+            .map(|step| Deduction::new(&logic, step))
+            .collect(),
+        solved: is_solved(&grid),
+    })
+}
+
+impl fmt::Display for Deduction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let clue: Vec<_> = self.clue.iter().map(u32::to_string).collect();
+        let clue = if clue.is_empty() {
+            "empty".to_string()
+        } else {
+            clue.join(" ")
+        };
+
+        let value = |filled| if filled { "filled" } else { "empty" };
+
+        if self.reason == Reason::Contradiction {
+            let cell = self.cells[0];
+
+            return write!(
+                f,
+                "cell ({}, {}) is {}: making it {} breaks {} [{clue}]",
+                cell.row + 1,
+                cell.col + 1,
+                value(cell.filled),
+                value(!cell.filled),
+                self.line,
+            );
+        }
+
+        let cells: Vec<_> = self
+            .cells
+            .iter()
+            .map(|cell| {
+                let i = match self.line {
+                    Line::Row(_) => cell.col,
+                    Line::Col(_) => cell.row,
+                };
+
+                format!("{} {}", i + 1, value(cell.filled))
+            })
+            .collect();
+
+        let reason = match self.reason {
+            Reason::Overlap => "leftmost and rightmost placements overlap",
+            _ => "no placement fits otherwise",
+        };
+
+        write!(f, "{} [{clue}]: {} ({reason})", self.line, cells.join(", "))
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, deduction) in self.deductions.iter().enumerate() {
+            writeln!(f, "{}. {deduction}", i + 1)?;
+        }
+
+        if !self.solved {
+            writeln!(f, "no further deductions, the remaining cells need a guess")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{common::clues, solvers, Vec2D};
+
+const SAMPLES: usize = 16;
+
+pub fn generate(width: usize, height: usize, density: f64, seed: u64) -> Option<Vec2D<bool>> {
+    let mut rng = Rng(seed);
+
+    for _ in 0..SAMPLES {
+        let mut grid: Vec2D<bool> = (0..height)
+            .map(|_| (0..width).map(|_| rng.float() < density).collect())
+            .collect();
+
+        for _ in 0..width * height {
+            let (rows, cols) = clues(&grid);
+
+            let solutions = solvers::solutions(rows, cols, 2);
+
+            let Some(other) = solutions.iter().find(|&s| *s != grid) else {
+                return Some(grid);
+            };
+
+            // flip a cell inside the ambiguous region and try again
+            let ambiguous: Vec<_> = (0..height)
+                .flat_map(|row| (0..width).map(move |col| (row, col)))
+                .filter(|&(row, col)| grid[row][col] != other[row][col])
+                .collect();
+
+            let (row, col) = ambiguous[rng.below(ambiguous.len())];
+            grid[row][col] = !grid[row][col];
+        }
+    }
+
+    None
+}
+
+// splitmix64, so that a seed gives the same puzzle on every platform
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    fn float(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
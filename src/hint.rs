@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    explain::{Cell, Deduction},
+    logic::{is_solved, Logic, Partial, Step, Tier},
+    solvers, Vec2D,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hint {
+    pub deduction: Option<Deduction>,
+    pub mistakes: Vec<Cell>,
+    // every cell is marked and some solution agrees with all of them
+    pub solved: bool,
+}
+
+pub fn hint(rows: Vec2D<u32>, cols: Vec2D<u32>, grid: &Partial) -> Option<Hint> {
+    let mistakes = mistakes(rows.clone(), cols.clone(), grid)?;
+
+    // deduce as if the wrong marks were never made
+    let mut grid = grid.clone();
+
+    for cell in &mistakes {
+        grid[cell.row][cell.col] = None;
+    }
+
+    let solved = mistakes.is_empty() && is_solved(&grid);
+
+    let logic = Logic::new(rows, cols);
+
+    let deduction = next(&logic, &grid).map(|step| Deduction::new(&logic, step));
+
+    Some(Hint {
+        deduction,
+        mistakes,
+        solved,
+    })
+}
+
+// the marks no solution can agree with, each with the value it should have;
+// None if the puzzle has no solution at all. A puzzle with several solutions
+// accepts the marks of any of them, so when the marks clash only the ones
+// that do not fit together with the rest are reported
+pub fn mistakes(rows: Vec2D<u32>, cols: Vec2D<u32>, grid: &Partial) -> Option<Vec<Cell>> {
+    if solvers::solve_partial(rows.clone(), cols.clone(), grid).is_some() {
+        return Some(Vec::new());
+    }
+
+    let solution = solvers::solutions(rows.clone(), cols.clone(), 1).pop()?;
+
+    // the marks that agree with one solution fit together; the others are
+    // kept one at a time for as long as some solution still agrees
+    let mut known: Partial = std::iter::zip(grid, &solution)
+        .map(|(marks, values)| {
+            std::iter::zip(marks, values)
+                .map(|(&mark, &value)| mark.filter(|&mark| mark == value))
+                .collect()
+        })
+        .collect();
+
+    let mut mistakes = Vec::new();
+
+    for (row, marks) in grid.iter().enumerate() {
+        for (col, &mark) in marks.iter().enumerate() {
+            let Some(filled) = mark.filter(|&mark| mark != solution[row][col]) else {
+                continue;
+            };
+
+            known[row][col] = Some(filled);
+
+            if solvers::solve_partial(rows.clone(), cols.clone(), &known).is_none() {
+                known[row][col] = None;
+                mistakes.push(Cell {
+                    row,
+                    col,
+                    filled: !filled,
+                });
+            }
+        }
+    }
+
+    Some(mistakes)
+}
+
+// the step of the lowest tier that fixes the most cells
+fn next(logic: &Logic, grid: &Partial) -> Option<Step> {
+    let best = |tier: Tier, values: &dyn Fn(_) -> Option<Vec<Option<bool>>>| {
+        logic
+            .lines()
+            .filter_map(|line| {
+                let values = values(line)?;
+                logic.apply(&mut grid.clone(), line, &values, tier)
+            })
+            .max_by_key(|step| step.cells.len())
+    };
+
+    best(Tier::Overlap, &|line| Some(logic.overlap(line)))
+        .or_else(|| best(Tier::Line, &|line| logic.solve_line(grid, line).ok()))
+        .or_else(|| {
+            let mut steps = Vec::new();
+            logic.probe(&mut grid.clone(), &mut steps).ok()?;
+            steps.into_iter().find(|step| step.tier == Tier::Probe)
+        })
+}
@@ -0,0 +1,204 @@
+use std::{io::Cursor, path::Path};
+
+use crate::Vec2D;
+
+pub fn load(path: &Path) -> Result<Vec2D<u8>, String> {
+    let data = std::fs::read(path).map_err(|e| format!("error reading {}: {e}", path.display()))?;
+
+    if data.starts_with(b"\x89PNG") {
+        decode_png(data)
+    } else if data.starts_with(b"P") {
+        decode_pnm(&data)
+    } else {
+        Err(format!("unsupported image format: {}", path.display()))
+    }
+}
+
+fn decode_png(data: Vec<u8>) -> Result<Vec2D<u8>, String> {
+    let mut decoder = png::Decoder::new(Cursor::new(data));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let size = reader.output_buffer_size().ok_or("image is too large")?;
+
+    let mut buf = vec![0; size];
+    let info = reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+
+    let channels = info.color_type.samples();
+    let width = info.width as usize;
+
+    let gray = |px: &[u8]| -> u8 {
+        let value = match info.color_type {
+            png::ColorType::Rgb | png::ColorType::Rgba => {
+                (px[0] as u32 * 299 + px[1] as u32 * 587 + px[2] as u32 * 114) / 1000
+            }
+            _ => px[0] as u32,
+        };
+
+        // transparent pixels count as background
+        match info.color_type {
+            png::ColorType::GrayscaleAlpha | png::ColorType::Rgba => {
+                let alpha = px[channels - 1] as u32;
+                (value * alpha + 255 * (255 - alpha)) / 255
+            }
+            _ => value,
+        }
+        .min(255) as u8
+    };
+
+    let pixels = buf[..info.buffer_size()]
+        .chunks(info.line_size)
+        .map(|line| line.chunks(channels).take(width).map(gray).collect())
+        .collect();
+
+    Ok(pixels)
+}
+
+fn decode_pnm(data: &[u8]) -> Result<Vec2D<u8>, String> {
+    let mut pos = 0;
+
+    let mut token = || -> Option<&[u8]> {
+        loop {
+            while data.get(pos)?.is_ascii_whitespace() {
+                pos += 1;
+            }
+
+            if data[pos] == b'#' {
+                while *data.get(pos)? != b'\n' {
+                    pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+
+        let start = pos;
+
+        while data.get(pos).is_some_and(|c| !c.is_ascii_whitespace()) {
+            pos += 1;
+        }
+
+        Some(&data[start..pos])
+    };
+
+    let number =
+        |token: Option<&[u8]>| -> Option<usize> { std::str::from_utf8(token?).ok()?.parse().ok() };
+
+    let magic = token().ok_or("missing image header")?.to_vec();
+
+    let width = number(token()).ok_or("invalid image width")?;
+    let height = number(token()).ok_or("invalid image height")?;
+    let size = width.checked_mul(height).ok_or("invalid image size")?;
+
+    let max = match magic.as_slice() {
+        b"P1" | b"P4" => 1,
+        b"P2" | b"P5" => number(token())
+            .filter(|&n| n > 0)
+            .ok_or("invalid maximum value")?,
+        _ => return Err("unsupported image format".to_string()),
+    };
+
+    let scale = |v: usize| (v.min(max) * 255 / max) as u8;
+
+    let values: Vec<u8> = match magic.as_slice() {
+        // in bitmaps 1 is black
+        b"P1" => (0..size)
+            .map(|_| number(token()).map(|v| 255 - scale(v)))
+            .collect::<Option<_>>()
+            .ok_or("truncated image data")?,
+
+        b"P2" => (0..size)
+            .map(|_| number(token()).map(scale))
+            .collect::<Option<_>>()
+            .ok_or("truncated image data")?,
+
+        b"P4" => {
+            let raster = data.get(pos + 1..).unwrap_or_default();
+            let stride = width.div_ceil(8);
+            let bytes = stride.checked_mul(height).ok_or("invalid image size")?;
+
+            if raster.len() < bytes {
+                return Err("truncated image data".to_string());
+            }
+
+            (0..height)
+                .flat_map(|y| {
+                    (0..width).map(move |x| {
+                        let bit = raster[y * stride + x / 8] >> (7 - x % 8) & 1;
+                        255 - bit * 255
+                    })
+                })
+                .collect()
+        }
+
+        b"P5" => {
+            let raster = data.get(pos + 1..).unwrap_or_default();
+            let depth = if max > 255 { 2 } else { 1 };
+            let bytes = size.checked_mul(depth).ok_or("invalid image size")?;
+
+            if raster.len() < bytes {
+                return Err("truncated image data".to_string());
+            }
+
+            raster
+                .chunks(depth)
+                .take(size)
+                .map(|px| scale(px.iter().fold(0, |acc, &b| acc << 8 | b as usize)))
+                .collect()
+        }
+
+        _ => unreachable!(),
+    };
+
+    if width == 0 {
+        return Ok(vec![Vec::new(); height]);
+    }
+
+    Ok(values.chunks(width).map(<[u8]>::to_vec).collect())
+}
+
+pub fn threshold(pixels: &Vec2D<u8>, level: u8) -> Vec2D<bool> {
+    pixels
+        .iter()
+        .map(|row| row.iter().map(|&px| px < level).collect())
+        .collect()
+}
+
+pub fn downsample(grid: &Vec2D<bool>, width: usize, height: usize) -> Result<Vec2D<bool>, String> {
+    let src_height = grid.len();
+    let src_width = grid.first().map_or(0, Vec::len);
+
+    // there are no pixels to cover the cells with
+    if src_width == 0 || src_height == 0 {
+        return Err("empty image".to_string());
+    }
+
+    // range of source pixels covered by target cell i
+    let span = |i: usize, src: usize, dst: usize| {
+        let start = i * src / dst;
+        start..((i + 1) * src / dst).max(start + 1)
+    };
+
+    // a cell is filled if at least half of the pixels it covers are
+    let cells = (0..height)
+        .map(|row| {
+            let ys = span(row, src_height, height);
+
+            (0..width)
+                .map(|col| {
+                    let xs = span(col, src_width, width);
+
+                    let total = ys.len() * xs.len();
+                    let filled = ys
+                        .clone()
+                        .flat_map(|y| xs.clone().filter(move |&x| grid[y][x]))
+                        .count();
+
+                    2 * filled >= total
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(cells)
+}
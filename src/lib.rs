@@ -1,9 +1,65 @@
+#![allow(clippy::needless_range_loop)]
+
+#[cfg(not(any(
+    feature = "kissat",
+    feature = "minisat",
+    feature = "varisat",
+    feature = "backtrack"
+)))]
+compile_error!(
+    "at least one of the `kissat`, `minisat`, `varisat` or `backtrack` features must be enabled"
+);
+
 pub(crate) type Vec2D<T> = Vec<Vec<T>>;
 
+#[cfg(feature = "minisat")]
+pub mod ambiguity;
+#[cfg(feature = "minisat")]
+pub mod backbone;
+pub mod batch;
+#[cfg(feature = "capi")]
+pub mod capi;
 pub mod common;
+pub mod explain;
+pub mod generate;
+pub mod hint;
+pub mod image;
+pub mod logic;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod rate;
+pub mod render;
+#[cfg(feature = "minisat")]
+pub mod repair;
+pub mod report;
+#[cfg(any(feature = "kissat", feature = "minisat", feature = "varisat"))]
+mod sat;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "minisat")]
+pub mod session;
+#[cfg(any(feature = "kissat", feature = "minisat", feature = "varisat"))]
 pub mod solver_automaton;
+#[cfg(feature = "minisat")]
 pub mod solver_automaton_minisat;
+#[cfg(feature = "backtrack")]
+pub mod solver_backtrack;
+#[cfg(any(feature = "kissat", feature = "varisat"))]
 pub mod solver_enclose;
+#[cfg(feature = "kissat")]
 pub mod solver_kissat;
+#[cfg(feature = "minisat")]
 pub mod solver_minisat;
+#[cfg(any(feature = "kissat", feature = "minisat", feature = "varisat"))]
+mod solver_placement;
+#[cfg(any(feature = "kissat", feature = "minisat", feature = "varisat"))]
+pub mod solver_probe;
+#[cfg(any(feature = "kissat", feature = "varisat"))]
 pub mod solver_starts;
+#[cfg(feature = "varisat")]
+pub mod solver_varisat;
+pub mod solvers;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,416 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{find_solutions, fits},
+    Vec2D,
+};
+
+pub type Partial = Vec2D<Option<bool>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tier {
+    Overlap,
+    Line,
+    Probe,
+    Backtrack,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Line {
+    Row(usize),
+    Col(usize),
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Line::Row(i) => write!(f, "row {}", i + 1),
+            Line::Col(i) => write!(f, "column {}", i + 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub tier: Tier,
+    pub line: Line,
+    pub cells: Vec<(usize, usize, bool)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contradiction(pub Line);
+
+pub struct Logic {
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    placements: Vec<Vec2D<bool>>,
+}
+
+impl Logic {
+    pub fn new(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Self {
+        let width = cols.len() as u32;
+        let height = rows.len() as u32;
+
+        let placements = rows
+            .iter()
+            .map(|row| find_solutions(row, width))
+            .chain(cols.iter().map(|col| find_solutions(col, height)))
+            .collect();
+
+        Logic {
+            rows,
+            cols,
+            placements,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.cols.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn empty(&self) -> Partial {
+        vec![vec![None; self.width()]; self.height()]
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = Line> {
+        (0..self.height())
+            .map(Line::Row)
+            .chain((0..self.width()).map(Line::Col))
+    }
+
+    pub fn clue(&self, line: Line) -> &[u32] {
+        match line {
+            Line::Row(i) => &self.rows[i],
+            Line::Col(i) => &self.cols[i],
+        }
+    }
+
+    fn placements(&self, line: Line) -> &Vec2D<bool> {
+        match line {
+            Line::Row(i) => &self.placements[i],
+            Line::Col(i) => &self.placements[self.height() + i],
+        }
+    }
+
+    pub fn cell(line: Line, i: usize) -> (usize, usize) {
+        match line {
+            Line::Row(row) => (row, i),
+            Line::Col(col) => (i, col),
+        }
+    }
+
+    pub fn get(&self, grid: &Partial, line: Line) -> Vec<Option<bool>> {
+        match line {
+            Line::Row(row) => grid[row].clone(),
+            Line::Col(col) => grid.iter().map(|row| row[col]).collect(),
+        }
+    }
+
+    // leftmost and rightmost packings of the blocks agree on a cell
+    pub fn overlap(&self, line: Line) -> Vec<Option<bool>> {
+        let clue = self.clue(line);
+        let len = match line {
+            Line::Row(_) => self.width(),
+            Line::Col(_) => self.height(),
+        };
+
+        // cell owners: 2k is the gap before block k, 2k + 1 is block k
+        let pack = |clue: &mut dyn Iterator<Item = &u32>| {
+            let mut owners = Vec::with_capacity(len);
+
+            for (k, &n) in clue.enumerate() {
+                if k > 0 {
+                    owners.push(2 * k);
+                }
+
+                owners.extend(std::iter::repeat_n(2 * k + 1, n as usize));
+            }
+
+            owners
+        };
+
+        let mut left = pack(&mut clue.iter());
+        left.resize(len, 2 * clue.len());
+
+        let mut right = pack(&mut clue.iter().rev());
+        right.resize(len, 2 * clue.len());
+        right.reverse();
+
+        // right was packed in reverse, so renumber its owners
+        let right = right.into_iter().map(|owner| 2 * clue.len() - owner);
+
+        std::iter::zip(left, right)
+            .map(|(l, r)| (l == r).then_some(l % 2 == 1))
+            .collect()
+    }
+
+    pub fn solve_line(
+        &self,
+        grid: &Partial,
+        line: Line,
+    ) -> Result<Vec<Option<bool>>, Contradiction> {
+        let known = self.get(grid, line);
+
+        let mut result: Option<Vec<Option<bool>>> = None;
+
+        let fits = self
+            .placements(line)
+            .iter()
+            .filter(|placement| fits(placement, &known));
+
+        for placement in fits {
+            match &mut result {
+                None => result = Some(placement.iter().map(|&p| Some(p)).collect()),
+                Some(result) => {
+                    for (r, &p) in std::iter::zip(result.iter_mut(), placement) {
+                        if *r != Some(p) {
+                            *r = None;
+                        }
+                    }
+                }
+            }
+        }
+
+        result.ok_or(Contradiction(line))
+    }
+
+    pub fn apply(
+        &self,
+        grid: &mut Partial,
+        line: Line,
+        values: &[Option<bool>],
+        tier: Tier,
+    ) -> Option<Step> {
+        let mut cells = Vec::new();
+
+        for (i, &value) in values.iter().enumerate() {
+            let (row, col) = Self::cell(line, i);
+
+            if let (None, Some(value)) = (grid[row][col], value) {
+                grid[row][col] = Some(value);
+                cells.push((row, col, value));
+            }
+        }
+
+        (!cells.is_empty()).then_some(Step { tier, line, cells })
+    }
+
+    pub fn propagate(
+        &self,
+        grid: &mut Partial,
+        dirty: impl IntoIterator<Item = Line>,
+        steps: &mut Vec<Step>,
+    ) -> Result<(), Contradiction> {
+        self.propagate_within(grid, dirty, steps, |_| true)
+    }
+
+    // like `propagate`, but only the lines `within` accepts are solved
+    fn propagate_within(
+        &self,
+        grid: &mut Partial,
+        dirty: impl IntoIterator<Item = Line>,
+        steps: &mut Vec<Step>,
+        within: impl Fn(Line) -> bool,
+    ) -> Result<(), Contradiction> {
+        let mut queue: VecDeque<_> = dirty.into_iter().filter(|&line| within(line)).collect();
+
+        while let Some(line) = queue.pop_front() {
+            let values = self.solve_line(grid, line)?;
+
+            if let Some(step) = self.apply(grid, line, &values, Tier::Line) {
+                for &(row, col, _) in &step.cells {
+                    let crossing = match line {
+                        Line::Row(_) => Line::Col(col),
+                        Line::Col(_) => Line::Row(row),
+                    };
+
+                    if within(crossing) && !queue.contains(&crossing) {
+                        queue.push_back(crossing);
+                    }
+                }
+
+                steps.push(step);
+            }
+        }
+
+        Ok(())
+    }
+
+    // the lines line solving needs to reach a contradiction from an empty
+    // grid using only `lines`, traced back from the line that failed through
+    // the steps that fixed its cells; None if it finds no contradiction
+    pub fn conflict(&self, lines: &[Line]) -> Option<Vec<Line>> {
+        let mut grid = self.empty();
+        let mut steps = Vec::new();
+
+        let set: HashSet<_> = lines.iter().copied().collect();
+        let within = |line| set.contains(&line);
+        let result = self.propagate_within(&mut grid, lines.to_vec(), &mut steps, within);
+
+        let Err(Contradiction(failed)) = result else {
+            return None;
+        };
+
+        // the step that fixed each cell
+        let mut reason = vec![vec![None; self.width()]; self.height()];
+
+        for (i, step) in steps.iter().enumerate() {
+            for &(row, col, _) in &step.cells {
+                reason[row][col] = Some(i);
+            }
+        }
+
+        let mut needed = vec![false; steps.len()];
+        let mut core = vec![failed];
+
+        // a line and the step up to which the cells fixed in it count
+        let mut pending = vec![(failed, steps.len())];
+
+        while let Some((line, before)) = pending.pop() {
+            for i in 0..self.get(&grid, line).len() {
+                let (row, col) = Self::cell(line, i);
+
+                let Some(step) = reason[row][col].filter(|&s| s < before && !needed[s]) else {
+                    continue;
+                };
+
+                needed[step] = true;
+
+                if !core.contains(&steps[step].line) {
+                    core.push(steps[step].line);
+                }
+
+                pending.push((steps[step].line, step));
+            }
+        }
+
+        Some(core)
+    }
+
+    // tries both values of every unknown cell, and fixes the first cell
+    // for which one of them leads to a contradiction
+    pub fn probe(&self, grid: &mut Partial, steps: &mut Vec<Step>) -> Result<bool, Contradiction> {
+        for row in 0..self.height() {
+            for col in 0..self.width() {
+                if grid[row][col].is_some() {
+                    continue;
+                }
+
+                for value in [true, false] {
+                    let mut trial = grid.clone();
+                    trial[row][col] = Some(value);
+
+                    let dirty = [Line::Row(row), Line::Col(col)];
+
+                    if let Err(Contradiction(line)) =
+                        self.propagate(&mut trial, dirty, &mut Vec::new())
+                    {
+                        grid[row][col] = Some(!value);
+
+                        steps.push(Step {
+                            tier: Tier::Probe,
+                            line,
+                            cells: vec![(row, col, !value)],
+                        });
+
+                        self.propagate(grid, dirty, steps)?;
+
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    // everything short of guessing: overlap, line solving, probing
+    pub fn deduce(&self, grid: &mut Partial, steps: &mut Vec<Step>) -> Result<(), Contradiction> {
+        for line in self.lines().collect::<Vec<_>>() {
+            let values = self.overlap(line);
+
+            if let Some(step) = self.apply(grid, line, &values, Tier::Overlap) {
+                steps.push(step);
+            }
+        }
+
+        self.propagate(grid, self.lines().collect::<Vec<_>>(), steps)?;
+
+        while self.probe(grid, steps)? {}
+
+        Ok(())
+    }
+}
+
+pub fn presolve(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Partial> {
+    let logic = Logic::new(rows, cols);
+
+    let mut grid = logic.empty();
+    logic.deduce(&mut grid, &mut Vec::new()).ok()?;
+
+    Some(grid)
+}
+
+pub fn is_solved(grid: &Partial) -> bool {
+    grid.iter().flatten().all(Option::is_some)
+}
+
+pub fn unwrap(grid: &Partial) -> Vec2D<bool> {
+    grid.iter()
+        .map(|row| row.iter().map(|c| c.unwrap_or(false)).collect())
+        .collect()
+}
+
+// same box as `common::format_grid`, with `?` for unknown cells, so that
+// `parse_partial` reads it back
+pub fn format_partial(grid: &Partial) -> String {
+    let width = grid.first().map_or(0, Vec::len);
+    let border = "═".repeat(width);
+
+    let mut out = format!("╔{border}╗\n");
+
+    for row in grid {
+        out.push('║');
+        out.extend(row.iter().map(|cell| match cell {
+            Some(true) => '#',
+            Some(false) => '.',
+            None => '?',
+        }));
+        out.push_str("║\n");
+    }
+
+    out.push_str(&format!("╚{border}╝\n"));
+
+    out
+}
+
+// one line per row: `#` filled, `.` empty, `?` unknown; borders as printed
+// by `common::display` are skipped
+pub fn parse_partial(input: &str) -> Option<Partial> {
+    input
+        .lines()
+        .map(|line| line.trim().trim_matches('║'))
+        .filter(|line| !line.is_empty() && !line.contains('═'))
+        .map(|line| {
+            line.chars()
+                .map(|c| match c {
+                    '#' => Some(Some(true)),
+                    '.' => Some(Some(false)),
+                    '?' => Some(None),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect()
+}
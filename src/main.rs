@@ -1,58 +1,1289 @@
-use std::{io, time::Instant};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
+};
 
-fn main() {
-    let input = match io::read_to_string(io::stdin()) {
-        Ok(string) => string,
-        Err(e) => {
-            eprintln!("error reading from stdin: {e}");
-            return;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use nonogram::{
+    common,
+    report::{Report, Stats, Status},
+    solvers,
+};
+
+#[derive(Parser)]
+#[command(name = "nonogram", version, about = "Nonogram solver and toolkit")]
+struct Cli {
+    /// Only print the requested output, no diagnostics
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve a puzzle
+    Solve(SolveArgs),
+    /// Check that a puzzle has exactly one solution
+    Check(CheckArgs),
+    /// Count the solutions of a puzzle
+    Count(CountArgs),
+    /// Convert a puzzle between formats, or turn an image into a puzzle
+    #[command(visible_alias = "from-image")]
+    Convert(ConvertArgs),
+    /// Generate a random puzzle with a unique solution
+    Generate(GenerateArgs),
+    /// Time solvers on one or more puzzles
+    Bench(BenchArgs),
+    /// Solve many puzzles in parallel and report one JSON line per puzzle
+    Batch(BatchArgs),
+    /// Render a puzzle or its solution as an image
+    Render(RenderArgs),
+    /// Rate the difficulty of a puzzle
+    Rate(ReportArgs),
+    /// Explain a puzzle step by step
+    Explain(ReportArgs),
+    /// Suggest the next step for a partially solved grid
+    Hint(HintArgs),
+    /// Suggest single clue edits that make a puzzle uniquely solvable
+    #[cfg(feature = "minisat")]
+    Repair(RepairArgs),
+    /// Show the cells where the solutions of a puzzle differ
+    #[cfg(feature = "minisat")]
+    Ambiguity(AmbiguityArgs),
+    /// Print the cells that are the same in every solution
+    #[cfg(feature = "minisat")]
+    Backbone(BackboneArgs),
+    /// List a minimal set of clues that together have no solution
+    #[cfg(feature = "minisat")]
+    Conflicts(ConflictsArgs),
+    /// Play a puzzle in the terminal
+    #[cfg(feature = "tui")]
+    Play(PlayArgs),
+    /// Serve the solver over HTTP
+    #[cfg(feature = "server")]
+    Serve(ServeArgs),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    Svg,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DataFormat {
+    Text,
+    Json,
+}
+
+#[derive(Args)]
+struct Input {
+    /// Puzzle file, stdin if missing or `-`
+    file: Option<PathBuf>,
+
+    /// Puzzle format, guessed from the input if not given
+    #[arg(long, value_enum)]
+    input_format: Option<DataFormat>,
+}
+
+#[derive(Args)]
+struct SolveArgs {
+    #[command(flatten)]
+    input: Input,
+
+    #[arg(long, default_value = solvers::DEFAULT)]
+    solver: String,
+
+    #[arg(long, visible_alias = "output-format", value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Write the output to a file; .png, .pbm and .pgm write an image
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Pixels per cell for image output
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    cell_size: u32,
+
+    /// Give up after this many seconds
+    #[arg(long, value_parser = seconds)]
+    timeout: Option<Duration>,
+
+    /// Re-solve the file whenever it changes, checking uniqueness
+    #[arg(long, conflicts_with = "output")]
+    watch: bool,
+}
+
+#[derive(Args)]
+struct CheckArgs {
+    #[command(flatten)]
+    input: Input,
+
+    #[arg(long, value_parser = seconds)]
+    timeout: Option<Duration>,
+}
+
+#[derive(Args)]
+struct CountArgs {
+    #[command(flatten)]
+    input: Input,
+
+    /// Stop counting after this many solutions
+    #[arg(long, default_value_t = 1000)]
+    limit: usize,
+
+    #[arg(long, value_enum, default_value_t = DataFormat::Text)]
+    format: DataFormat,
+
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    #[arg(long, value_parser = seconds)]
+    timeout: Option<Duration>,
+}
+
+#[derive(Args)]
+struct ConvertArgs {
+    /// Puzzle file or image (.png, .pbm, .pgm), stdin if missing or `-`
+    file: Option<PathBuf>,
+
+    #[arg(long, value_enum)]
+    input_format: Option<DataFormat>,
+
+    /// Output format; svg draws the blank puzzle
+    #[arg(long, visible_alias = "output-format", value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Puzzle width for images, keeps the aspect ratio if only one is given
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    width: Option<u32>,
+
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    height: Option<u32>,
+
+    /// Pixels darker than this are filled
+    #[arg(long, default_value_t = 128)]
+    threshold: u8,
+
+    /// Report whether the converted puzzle has a unique solution
+    #[arg(long)]
+    check: bool,
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    #[arg(long, default_value_t = 10)]
+    width: usize,
+
+    #[arg(long, default_value_t = 10)]
+    height: usize,
+
+    /// Share of filled cells to start from, between 0 and 1
+    #[arg(long, default_value_t = 0.5, value_parser = density)]
+    density: f64,
+
+    /// Seed for a reproducible puzzle, random if not given
+    #[arg(long)]
+    seed: Option<u64>,
+
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    /// Puzzle files, stdin if none
+    files: Vec<PathBuf>,
+
+    #[arg(long, value_enum)]
+    input_format: Option<DataFormat>,
+
+    /// Solver to run, may be repeated; all solvers if not given
+    #[arg(long)]
+    solver: Vec<String>,
+
+    #[arg(long, value_enum, default_value_t = DataFormat::Text)]
+    format: DataFormat,
+
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Time limit for each solver run
+    #[arg(long, value_parser = seconds)]
+    timeout: Option<Duration>,
+}
+
+#[derive(Args)]
+struct BatchArgs {
+    /// Puzzle files, directories or files with several puzzles; stdin if none
+    paths: Vec<PathBuf>,
+
+    #[arg(long, default_value = solvers::DEFAULT)]
+    solver: String,
+
+    /// Number of worker threads, one per core if not given
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    jobs: Option<u32>,
+
+    /// Write the JSON lines to a file
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Time limit for each puzzle; only backtrack is stopped, other solvers
+    /// keep their worker until they finish
+    #[arg(long, value_parser = seconds)]
+    timeout: Option<Duration>,
+}
+
+#[derive(Args)]
+struct RenderArgs {
+    #[command(flatten)]
+    input: Input,
+
+    #[arg(long, default_value = solvers::DEFAULT)]
+    solver: String,
+
+    /// Image file (.svg, .png, .pbm, .pgm), svg on stdout if missing
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Pixels per cell for .png, .pbm and .pgm
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    cell_size: u32,
+
+    /// Draw only the clues and an empty grid
+    #[arg(long)]
+    blank: bool,
+
+    #[arg(long, value_parser = seconds)]
+    timeout: Option<Duration>,
+}
+
+#[derive(Args)]
+struct ReportArgs {
+    #[command(flatten)]
+    input: Input,
+
+    #[arg(long, value_enum, default_value_t = DataFormat::Text)]
+    format: DataFormat,
+
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct HintArgs {
+    #[command(flatten)]
+    input: Input,
+
+    /// Current grid, with `#` filled, `.` empty and `?` unknown
+    #[arg(long)]
+    grid: PathBuf,
+
+    #[arg(long, value_enum, default_value_t = DataFormat::Text)]
+    format: DataFormat,
+
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[cfg(feature = "minisat")]
+#[derive(Args)]
+struct RepairArgs {
+    #[command(flatten)]
+    input: Input,
+
+    /// Intended picture, an image or a grid with `#` filled and `.` empty;
+    /// reports the lines whose clues disagree with it instead
+    #[arg(long)]
+    picture: Option<PathBuf>,
+
+    /// Stop after this many suggestions
+    #[arg(long, default_value_t = 10)]
+    limit: usize,
+
+    #[arg(long, value_enum, default_value_t = DataFormat::Text)]
+    format: DataFormat,
+
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[cfg(feature = "minisat")]
+#[derive(Args)]
+struct AmbiguityArgs {
+    #[command(flatten)]
+    input: Input,
+
+    /// svg shades the cells where solutions differ
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    #[arg(long, value_parser = seconds)]
+    timeout: Option<Duration>,
+}
+
+#[cfg(feature = "minisat")]
+#[derive(Args)]
+struct BackboneArgs {
+    #[command(flatten)]
+    input: Input,
+
+    #[arg(long, value_enum, default_value_t = DataFormat::Text)]
+    format: DataFormat,
+
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    #[arg(long, value_parser = seconds)]
+    timeout: Option<Duration>,
+}
+
+#[cfg(feature = "minisat")]
+#[derive(Args)]
+struct ConflictsArgs {
+    #[command(flatten)]
+    input: Input,
+
+    #[arg(long, value_parser = seconds)]
+    timeout: Option<Duration>,
+}
+
+#[cfg(feature = "tui")]
+#[derive(Args)]
+struct PlayArgs {
+    #[command(flatten)]
+    input: Input,
+
+    /// Progress file, loaded on start if it exists and written on save
+    #[arg(long)]
+    progress: Option<PathBuf>,
+}
+
+#[cfg(feature = "server")]
+#[derive(Args)]
+struct ServeArgs {
+    /// Address to listen on; port 0 picks a free port
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: String,
+
+    /// Number of requests solved at once, one per core if not given; further
+    /// requests get 503 until one finishes
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    workers: Option<u32>,
+
+    /// Time limit for each request
+    #[arg(long, default_value = "30", value_parser = seconds)]
+    timeout: Duration,
+}
+
+fn seconds(arg: &str) -> Result<Duration, String> {
+    match arg.parse::<f64>() {
+        Ok(secs) if secs > 0.0 && secs.is_finite() => Ok(Duration::from_secs_f64(secs)),
+        _ => Err("expected a positive number of seconds".to_string()),
+    }
+}
+
+fn density(arg: &str) -> Result<f64, String> {
+    match arg.parse::<f64>() {
+        Ok(d) if (0.0..=1.0).contains(&d) => Ok(d),
+        _ => Err("expected a number between 0 and 1".to_string()),
+    }
+}
+
+enum Failure {
+    Unsat,
+    // unsolvable, already reported along with the lines that conflict
+    #[cfg(feature = "minisat")]
+    Conflict,
+    Ambiguous,
+    Invalid(String),
+    Timeout,
+}
+
+impl Failure {
+    fn code(&self) -> u8 {
+        match self {
+            Failure::Unsat | Failure::Ambiguous => 1,
+            #[cfg(feature = "minisat")]
+            Failure::Conflict => 1,
+            Failure::Invalid(_) => 2,
+            Failure::Timeout => 3,
         }
+    }
+}
+
+type Outcome = Result<(), Failure>;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let quiet = cli.quiet;
+
+    let outcome = match cli.command {
+        Command::Solve(args) => solve(args, quiet),
+        Command::Check(args) => check(args),
+        Command::Count(args) => count(args, quiet),
+        Command::Convert(args) => convert(args, quiet),
+        Command::Generate(args) => generate(args, quiet),
+        Command::Bench(args) => bench(args),
+        Command::Batch(args) => batch(args, quiet),
+        Command::Render(args) => render(args),
+        Command::Rate(args) => rate(args),
+        Command::Explain(args) => explain(args),
+        Command::Hint(args) => hint(args),
+        #[cfg(feature = "minisat")]
+        Command::Repair(args) => repair(args),
+        #[cfg(feature = "minisat")]
+        Command::Ambiguity(args) => ambiguity(args),
+        #[cfg(feature = "minisat")]
+        Command::Backbone(args) => backbone(args),
+        #[cfg(feature = "minisat")]
+        Command::Conflicts(args) => conflicts(args, quiet),
+        #[cfg(feature = "tui")]
+        Command::Play(args) => play(args),
+        #[cfg(feature = "server")]
+        Command::Serve(args) => serve(args, quiet),
+    };
+
+    let Err(failure) = outcome else {
+        return ExitCode::SUCCESS;
+    };
+
+    match &failure {
+        Failure::Invalid(e) => eprintln!("{e}"),
+        _ if quiet => {}
+        Failure::Unsat => eprintln!("UNSOLVABLE"),
+        #[cfg(feature = "minisat")]
+        Failure::Conflict => {}
+        Failure::Ambiguous => eprintln!("NOT UNIQUE"),
+        Failure::Timeout => eprintln!("TIMEOUT"),
+    }
+
+    ExitCode::from(failure.code())
+}
+
+type Clues = (Vec<Vec<u32>>, Vec<Vec<u32>>);
+
+fn read(file: Option<&Path>) -> Result<String, Failure> {
+    match file {
+        Some(path) if path != Path::new("-") => fs::read_to_string(path)
+            .map_err(|e| Failure::Invalid(format!("error reading {}: {e}", path.display()))),
+        _ => io::read_to_string(io::stdin())
+            .map_err(|e| Failure::Invalid(format!("error reading from stdin: {e}"))),
+    }
+}
+
+fn parse(input: String, format: Option<DataFormat>) -> Result<Clues, Failure> {
+    let format = format.unwrap_or(if input.trim_start().starts_with('{') {
+        DataFormat::Json
+    } else {
+        DataFormat::Text
+    });
+
+    let parsed = match format {
+        DataFormat::Text => common::parse_checked(&input),
+        DataFormat::Json => common::parse_json_checked(&input),
     };
 
-    let (rows, cols) = match nonogram::common::parse(input) {
-        Some(nonogram) => nonogram,
+    parsed.map_err(|e| Failure::Invalid(format!("incorrect nonogram: {e}")))
+}
+
+fn read_puzzle(input: &Input) -> Result<Clues, Failure> {
+    parse(read(input.file.as_deref())?, input.input_format)
+}
+
+fn write(output: Option<&Path>, data: &[u8]) -> Outcome {
+    match output {
+        Some(path) => fs::write(path, data)
+            .map_err(|e| Failure::Invalid(format!("error writing {}: {e}", path.display()))),
         None => {
-            eprintln!("error parsing nonogram");
-            return;
+            use io::Write;
+
+            io::stdout()
+                .write_all(data)
+                .map_err(|e| Failure::Invalid(format!("error writing to stdout: {e}")))
         }
+    }
+}
+
+fn solver(name: &str) -> Result<solvers::Solve, Failure> {
+    solvers::get(name).ok_or_else(|| {
+        let names: Vec<_> = solvers::names().collect();
+        Failure::Invalid(format!(
+            "wrong solver name, expected one of: {}",
+            names.join(", ")
+        ))
+    })
+}
+
+fn run<T: Send + 'static>(
+    timeout: Option<Duration>,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, Failure> {
+    run_detached(timeout, f).0
+}
+
+// like `run`, but also hands back the thread `f` runs on, which is still
+// running if it timed out
+fn run_detached<T: Send + 'static>(
+    timeout: Option<Duration>,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> (Result<T, Failure>, Option<JoinHandle<()>>) {
+    let Some(timeout) = timeout else {
+        return (Ok(f()), None);
     };
 
-    if let Err(e) = nonogram::common::validate(&rows, &cols) {
-        eprintln!("incorrect nonogram: {e}");
-        return;
+    let (tx, rx) = mpsc::channel();
+
+    let thread = thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+
+    let result = rx.recv_timeout(timeout).map_err(|e| match e {
+        RecvTimeoutError::Timeout => Failure::Timeout,
+        RecvTimeoutError::Disconnected => Failure::Invalid("solver crashed".into()),
+    });
+
+    (result, Some(thread))
+}
+
+fn is_image(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("png" | "pbm" | "pgm")
+    )
+}
+
+fn puzzle_text(rows: &Vec<Vec<u32>>, cols: &Vec<Vec<u32>>, format: Format) -> String {
+    match format {
+        Format::Text => common::serialize(rows, cols),
+        Format::Json => common::serialize_json(rows, cols) + "\n",
+        Format::Svg => nonogram::render::svg(rows, cols, None),
+    }
+}
+
+fn solve(args: SolveArgs, quiet: bool) -> Outcome {
+    let solve = solver(&args.solver)?;
+
+    if args.watch {
+        return watch(&args, solve);
+    }
+
+    // an image output decides the format, so only the default goes with it
+    if let Some(path) = args.output.as_deref().filter(|path| is_image(path)) {
+        if args.format != Format::Text {
+            return Err(Failure::Invalid(format!(
+                "{} is an image, --format only applies to other outputs",
+                path.display()
+            )));
+        }
     }
 
     let start = Instant::now();
+    let (rows, cols) = read_puzzle(&args.input)?;
+    let parsed = start.elapsed();
+
+    let (width, height) = (cols.len(), rows.len());
+    let clues = (rows.clone(), cols.clone());
+
+    let start = Instant::now();
+    let result = run(args.timeout, move || solve(rows, cols));
+    let elapsed = start.elapsed();
 
-    let solution = if let Some(solver) = std::env::args().nth(1) {
-        if solver == "minisat" {
-            nonogram::solver_minisat::solve(rows, cols)
-        } else if solver == "kissat" {
-            nonogram::solver_kissat::solve(rows, cols)
-        } else if solver == "automaton" {
-            nonogram::solver_automaton::solve(rows, cols)
-        } else if solver == "automaton-minisat" {
-            nonogram::solver_automaton_minisat::solve(rows, cols)
-        } else if solver == "enclose" {
-            nonogram::solver_enclose::solve(rows, cols)
-        } else if solver == "starts" {
-            nonogram::solver_starts::solve(rows, cols)
-        } else {
-            eprintln!("wrong solver name");
-            return;
+    if args.format == Format::Json {
+        let solution = match &result {
+            Ok(solution) => solution.clone(),
+            Err(Failure::Timeout) => None,
+            Err(_) => return result.map(|_| ()),
+        };
+
+        let mut report = Report::new(&args.solver, width, height, solution);
+        report.stats = Stats::new(parsed, elapsed);
+
+        if result.is_err() {
+            report.status = Status::Timeout;
         }
-    } else {
-        nonogram::solver_kissat::solve(rows, cols)
+
+        write(args.output.as_deref(), (report.to_json() + "\n").as_bytes())?;
+    }
+
+    let solution = result?.ok_or(Failure::Unsat)?;
+
+    match (args.format, args.output.as_deref()) {
+        (Format::Json, _) => {}
+        (_, Some(path)) if is_image(path) => {
+            nonogram::render::write_image(path, &solution, args.cell_size as usize)
+                .map_err(Failure::Invalid)?
+        }
+        (Format::Svg, output) => {
+            let svg = nonogram::render::svg(&clues.0, &clues.1, Some(&solution));
+            write(output, svg.as_bytes())?
+        }
+        (Format::Text, output) => {
+            let text = format!("SOLUTION:\n{}", common::format_grid(&solution));
+            write(output, text.as_bytes())?
+        }
+    }
+
+    if !quiet {
+        eprintln!("TIME:\n{elapsed:?}");
+    }
+
+    Ok(())
+}
+
+const POLL: Duration = Duration::from_millis(250);
+
+fn watch(args: &SolveArgs, solve: solvers::Solve) -> Outcome {
+    let path = args
+        .input
+        .file
+        .clone()
+        .filter(|path| path != Path::new("-"))
+        .ok_or_else(|| Failure::Invalid("--watch expects a puzzle file".into()))?;
+
+    if args.format != Format::Text {
+        return Err(Failure::Invalid("--watch only prints text".into()));
+    }
+
+    let draw = |status: &str| {
+        // clear the screen and redraw from the top left corner
+        print!("\x1b[2J\x1b[H");
+        println!("WATCHING:\n{} (Ctrl-C to stop)", path.display());
+        print!("{status}");
+
+        io::Write::flush(&mut io::stdout())
+            .map_err(|e| Failure::Invalid(format!("error writing to stdout: {e}")))
+    };
+
+    let mut last = None;
+    // the thread of the last solve, still running if it timed out
+    let mut solving: Option<JoinHandle<()>> = None;
+
+    loop {
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+
+        if last.is_none() || modified != last {
+            last = modified;
+
+            // solvers cannot be interrupted, so rather than pile up threads
+            // every re-solve waits for the one before it
+            if let Some(previous) = solving.take() {
+                if !previous.is_finished() {
+                    draw("TIMEOUT\nWAITING:\nfor the last solve to finish\n")?;
+                }
+
+                let _ = previous.join();
+            }
+
+            let (status, thread) = watch_status(args, solve, &path);
+            solving = thread;
+
+            draw(&status)?;
+        }
+
+        thread::sleep(POLL);
+    }
+}
+
+fn watch_status(
+    args: &SolveArgs,
+    solve: solvers::Solve,
+    path: &Path,
+) -> (String, Option<JoinHandle<()>>) {
+    let puzzle = read(Some(path)).and_then(|input| parse(input, args.input.input_format));
+
+    let start = Instant::now();
+
+    let (result, thread) = match puzzle {
+        Ok((rows, cols)) => run_detached(args.timeout, move || {
+            let solution = solve(rows.clone(), cols.clone())?;
+            let count = solvers::solutions(rows, cols, 2).len();
+            Some((solution, count))
+        }),
+        Err(failure) => (Err(failure), None),
     };
 
     let elapsed = start.elapsed();
 
-    if let Some(solution) = solution {
-        println!("SOLUTION:");
-        nonogram::common::display(&solution);
+    let status = match result {
+        Ok(Some((solution, count))) => format!(
+            "SOLUTION:\n{}{}\nTIME:\n{elapsed:?}\n",
+            common::format_grid(&solution),
+            if count > 1 { "NOT UNIQUE" } else { "UNIQUE" },
+        ),
+        Ok(None) | Err(Failure::Unsat) => "UNSOLVABLE\n".to_string(),
+        #[cfg(feature = "minisat")]
+        Err(Failure::Conflict) => "UNSOLVABLE\n".to_string(),
+        Err(Failure::Ambiguous) => "NOT UNIQUE\n".to_string(),
+        Err(Failure::Timeout) => "TIMEOUT\n".to_string(),
+        Err(Failure::Invalid(e)) => format!("ERROR:\n{e}\n"),
+    };
 
-        eprintln!("TIME:\n{elapsed:?}");
+    (status, thread)
+}
+
+fn check(args: CheckArgs) -> Outcome {
+    let (rows, cols) = read_puzzle(&args.input)?;
+
+    match run(args.timeout, move || {
+        solvers::solutions(rows, cols, 2).len()
+    })? {
+        0 => Err(Failure::Unsat),
+        1 => {
+            println!("UNIQUE");
+            Ok(())
+        }
+        _ => Err(Failure::Ambiguous),
+    }
+}
+
+fn count(args: CountArgs, quiet: bool) -> Outcome {
+    let (rows, cols) = read_puzzle(&args.input)?;
+
+    let limit = args.limit;
+    let count = run(args.timeout, move || {
+        solvers::solutions(rows, cols, limit).len()
+    })?;
+
+    let text = match args.format {
+        DataFormat::Text => format!("{count}\n"),
+        DataFormat::Json => format!(
+            "{}\n",
+            serde_json::json!({ "count": count, "limit": limit })
+        ),
+    };
+
+    write(args.output.as_deref(), text.as_bytes())?;
+
+    if count == limit && !quiet {
+        eprintln!("LIMIT REACHED");
+    }
+
+    if count == 0 {
+        return Err(Failure::Unsat);
+    }
+
+    Ok(())
+}
+
+fn convert(args: ConvertArgs, quiet: bool) -> Outcome {
+    let (rows, cols) = match &args.file {
+        Some(path) if is_image(path) => from_image(path, &args)?,
+        file => parse(read(file.as_deref())?, args.input_format)?,
+    };
+
+    write(
+        args.output.as_deref(),
+        puzzle_text(&rows, &cols, args.format).as_bytes(),
+    )?;
+
+    if !args.check {
+        return Ok(());
+    }
+
+    match solvers::solutions(rows, cols, 2).len() {
+        0 => Err(Failure::Unsat),
+        1 => {
+            if !quiet {
+                eprintln!("UNIQUE");
+            }
+            Ok(())
+        }
+        _ => Err(Failure::Ambiguous),
+    }
+}
+
+fn from_image(path: &Path, args: &ConvertArgs) -> Result<Clues, Failure> {
+    let pixels = nonogram::image::load(path).map_err(Failure::Invalid)?;
+
+    let mut grid = nonogram::image::threshold(&pixels, args.threshold);
+
+    let src_height = grid.len();
+    let src_width = grid.first().map_or(0, Vec::len);
+
+    let width = args.width.map(|w| w as usize);
+    let height = args.height.map(|h| h as usize);
+
+    // keep the aspect ratio when only one dimension is given
+    let size = match (width, height) {
+        (Some(w), Some(h)) => Some((w, h)),
+        (Some(w), None) => Some((w, (src_height * w / src_width.max(1)).max(1))),
+        (None, Some(h)) => Some(((src_width * h / src_height.max(1)).max(1), h)),
+        (None, None) => None,
+    };
+
+    if let Some((w, h)) = size {
+        grid = nonogram::image::downsample(&grid, w, h).map_err(Failure::Invalid)?;
+    }
+
+    Ok(common::clues(&grid))
+}
+
+fn generate(args: GenerateArgs, quiet: bool) -> Outcome {
+    let seed = args.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64)
+    });
+
+    if !quiet {
+        eprintln!("SEED:\n{seed}");
+    }
+
+    let grid = nonogram::generate::generate(args.width, args.height, args.density, seed)
+        .ok_or_else(|| Failure::Invalid("could not generate a unique puzzle".into()))?;
+
+    let (rows, cols) = common::clues(&grid);
+
+    write(
+        args.output.as_deref(),
+        puzzle_text(&rows, &cols, args.format).as_bytes(),
+    )
+}
+
+fn bench(args: BenchArgs) -> Outcome {
+    let names: Vec<String> = if args.solver.is_empty() {
+        solvers::names().map(String::from).collect()
+    } else {
+        args.solver.clone()
+    };
+
+    let solves = names
+        .iter()
+        .map(|name| solver(name))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let files: Vec<Option<&Path>> = if args.files.is_empty() {
+        vec![None]
     } else {
-        eprintln!("UNSOLVABLE");
+        args.files.iter().map(|f| Some(f.as_path())).collect()
+    };
+
+    let mut out = String::new();
+
+    if args.format == DataFormat::Text {
+        out.push_str(&format!(
+            "{:<24} {:<18} {:<8} {:>12}\n",
+            "PUZZLE", "SOLVER", "STATUS", "TIME"
+        ));
+    }
+
+    for file in files {
+        let (rows, cols) = parse(read(file)?, args.input_format)?;
+        let label = file.map_or("-".to_string(), |f| f.display().to_string());
+
+        for (name, &solve) in names.iter().zip(&solves) {
+            let (r, c) = (rows.clone(), cols.clone());
+
+            let start = Instant::now();
+            let result = run(args.timeout, move || solve(r, c));
+            let elapsed = start.elapsed();
+
+            let (status, solution) = match result {
+                Ok(Some(solution)) => (Status::Sat, Some(solution)),
+                Ok(None) => (Status::Unsat, None),
+                Err(Failure::Timeout) => (Status::Timeout, None),
+                Err(failure) => return Err(failure),
+            };
+
+            match args.format {
+                DataFormat::Text => {
+                    let status = serde_json::to_value(status).unwrap();
+                    out.push_str(&format!(
+                        "{label:<24} {name:<18} {:<8} {:>12}\n",
+                        status.as_str().unwrap_or_default(),
+                        format!("{elapsed:.2?}")
+                    ));
+                }
+                DataFormat::Json => {
+                    let mut report = Report::new(name, cols.len(), rows.len(), solution);
+                    report.status = status;
+                    report.grid = None;
+                    report.stats = Stats::new(Duration::ZERO, elapsed);
+                    out.push_str(&(report.to_json() + "\n"));
+                }
+            }
+        }
+    }
+
+    write(args.output.as_deref(), out.as_bytes())
+}
+
+fn batch(args: BatchArgs, quiet: bool) -> Outcome {
+    use io::Write;
+
+    let solve = solver(&args.solver)?;
+
+    let jobs = if args.paths.is_empty() {
+        nonogram::batch::jobs("-", &read(None)?)
+    } else {
+        nonogram::batch::load(&args.paths)
+    };
+
+    let workers = args.jobs.map_or_else(
+        || thread::available_parallelism().map_or(1, |n| n.get()),
+        |n| n as usize,
+    );
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(
+            fs::File::create(path)
+                .map_err(|e| Failure::Invalid(format!("error writing {}: {e}", path.display())))?,
+        ),
+        None => Box::new(io::stdout()),
+    };
+
+    let mut written = Ok(());
+
+    let summary =
+        nonogram::batch::run(jobs, &args.solver, solve, workers, args.timeout, |record| {
+            if written.is_ok() {
+                written = writeln!(out, "{}", serde_json::to_string(record).unwrap());
+            }
+        });
+
+    written.map_err(|e| Failure::Invalid(format!("error writing output: {e}")))?;
+
+    if !quiet {
+        eprint!("{summary}");
+    }
+
+    // the worst status decides the exit code
+    if summary.invalid > 0 {
+        Err(Failure::Invalid(format!(
+            "{} invalid puzzles",
+            summary.invalid
+        )))
+    } else if summary.timeout > 0 {
+        Err(Failure::Timeout)
+    } else if summary.unsat > 0 {
+        Err(Failure::Unsat)
+    } else {
+        Ok(())
+    }
+}
+
+fn render(args: RenderArgs) -> Outcome {
+    let (rows, cols) = read_puzzle(&args.input)?;
+
+    let output = args.output.as_deref();
+
+    if args.blank {
+        if output.is_some_and(is_image) {
+            return Err(Failure::Invalid("--blank only renders svg".into()));
+        }
+
+        return write(output, nonogram::render::svg(&rows, &cols, None).as_bytes());
+    }
+
+    let solve = solver(&args.solver)?;
+
+    let clues = (rows.clone(), cols.clone());
+    let solution = run(args.timeout, move || solve(rows, cols))?.ok_or(Failure::Unsat)?;
+
+    match output {
+        Some(path) if is_image(path) => {
+            nonogram::render::write_image(path, &solution, args.cell_size as usize)
+                .map_err(Failure::Invalid)
+        }
+        _ => {
+            let svg = nonogram::render::svg(&clues.0, &clues.1, Some(&solution));
+            write(output, svg.as_bytes())
+        }
+    }
+}
+
+fn rate(args: ReportArgs) -> Outcome {
+    let (rows, cols) = read_puzzle(&args.input)?;
+
+    let rating = nonogram::rate::rate(rows, cols).ok_or(Failure::Unsat)?;
+
+    let text = match args.format {
+        DataFormat::Json => serde_json::to_string(&rating).unwrap() + "\n",
+        DataFormat::Text => format!(
+            "TIER:\n{:?}\nSTEPS:\n- {} overlap\n- {} line\n- {} probe\n- {} guesses (depth {})\nSCORE:\n{}\n",
+            rating.tier,
+            rating.overlap,
+            rating.line,
+            rating.probe,
+            rating.guesses,
+            rating.depth,
+            rating.score
+        ),
+    };
+
+    write(args.output.as_deref(), text.as_bytes())
+}
+
+fn explain(args: ReportArgs) -> Outcome {
+    let (rows, cols) = read_puzzle(&args.input)?;
+
+    let explanation = nonogram::explain::explain(rows, cols).ok_or(Failure::Unsat)?;
+
+    let text = match args.format {
+        DataFormat::Json => serde_json::to_string(&explanation).unwrap() + "\n",
+        DataFormat::Text => explanation.to_string(),
+    };
+
+    write(args.output.as_deref(), text.as_bytes())
+}
+
+fn hint(args: HintArgs) -> Outcome {
+    let grid = fs::read_to_string(&args.grid)
+        .map_err(|e| Failure::Invalid(format!("error reading {}: {e}", args.grid.display())))?;
+
+    let (rows, cols) = read_puzzle(&args.input)?;
+
+    let grid = nonogram::logic::parse_partial(&grid)
+        .filter(|grid| grid.len() == rows.len() && grid.iter().all(|row| row.len() == cols.len()))
+        .ok_or_else(|| Failure::Invalid("error parsing grid".into()))?;
+
+    let hint = nonogram::hint::hint(rows, cols, &grid).ok_or(Failure::Unsat)?;
+
+    let text = match args.format {
+        DataFormat::Json => serde_json::to_string(&hint).unwrap() + "\n",
+        DataFormat::Text => {
+            let mut text = String::from("HINT:\n");
+
+            match &hint.deduction {
+                Some(deduction) => text.push_str(&format!("{deduction}\n")),
+                None if hint.solved => text.push_str("solved\n"),
+                None => text.push_str("no logical deduction, a guess is needed\n"),
+            }
+
+            if !hint.mistakes.is_empty() {
+                text.push_str("MISTAKES:\n");
+
+                for cell in &hint.mistakes {
+                    text.push_str(&format!("- ({}, {})\n", cell.row + 1, cell.col + 1));
+                }
+            }
+
+            text
+        }
+    };
+
+    write(args.output.as_deref(), text.as_bytes())
+}
+
+#[cfg(feature = "minisat")]
+fn repair(args: RepairArgs) -> Outcome {
+    use nonogram::repair;
+
+    let (rows, cols) = read_puzzle(&args.input)?;
+
+    if let Some(path) = &args.picture {
+        let picture = read_picture(path, cols.len(), rows.len())?;
+        let mismatches = repair::mismatches(&rows, &cols, &picture);
+
+        let text = match args.format {
+            DataFormat::Json => serde_json::json!({ "mismatches": mismatches }).to_string() + "\n",
+            DataFormat::Text if mismatches.is_empty() => "MATCHES PICTURE\n".to_string(),
+            DataFormat::Text => {
+                let lines: String = mismatches.iter().map(|m| format!("- {m}\n")).collect();
+                format!("MISMATCHES:\n{lines}")
+            }
+        };
+
+        return write(args.output.as_deref(), text.as_bytes());
+    }
+
+    let (count, repairs) = repair::repair(rows, cols, args.limit);
+
+    let text = match args.format {
+        DataFormat::Json => {
+            serde_json::json!({ "solutions": count, "repairs": repairs }).to_string() + "\n"
+        }
+        DataFormat::Text if count == 1 => "UNIQUE\n".to_string(),
+        DataFormat::Text if repairs.is_empty() => "NO SINGLE EDIT REPAIR\n".to_string(),
+        DataFormat::Text => {
+            let lines: String = repairs.iter().map(|r| format!("- {r}\n")).collect();
+            format!("REPAIRS:\n{lines}")
+        }
+    };
+
+    write(args.output.as_deref(), text.as_bytes())?;
+
+    match (count, repairs.is_empty()) {
+        (0, true) => Err(Failure::Unsat),
+        (2, true) => Err(Failure::Ambiguous),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(feature = "minisat")]
+fn ambiguity(args: AmbiguityArgs) -> Outcome {
+    let (rows, cols) = read_puzzle(&args.input)?;
+    let clues = (rows.clone(), cols.clone());
+
+    let ambiguity = run(args.timeout, move || {
+        nonogram::ambiguity::ambiguity(rows, cols)
+    })?
+    .ok_or(Failure::Unsat)?;
+
+    let text = match args.format {
+        Format::Text => ambiguity.to_string(),
+        Format::Json => serde_json::to_string(&ambiguity).unwrap() + "\n",
+        Format::Svg => nonogram::render::svg_partial(&clues.0, &clues.1, &ambiguity.grid),
+    };
+
+    write(args.output.as_deref(), text.as_bytes())
+}
+
+#[cfg(feature = "minisat")]
+fn backbone(args: BackboneArgs) -> Outcome {
+    let (rows, cols) = read_puzzle(&args.input)?;
+
+    let grid = run(args.timeout, move || {
+        nonogram::backbone::backbone(rows, cols)
+    })?
+    .ok_or(Failure::Unsat)?;
+
+    let text = match args.format {
+        DataFormat::Text => format!("BACKBONE:\n{}", nonogram::logic::format_partial(&grid)),
+        DataFormat::Json => serde_json::to_string(&grid).unwrap() + "\n",
+    };
+
+    write(args.output.as_deref(), text.as_bytes())
+}
+
+// says UNSOLVABLE as soon as that is known, the search for the conflicting
+// lines can take a while longer
+#[cfg(feature = "minisat")]
+fn conflicts(args: ConflictsArgs, quiet: bool) -> Outcome {
+    use nonogram::{logic::Line, session::Session};
+
+    let (rows, cols) = read_puzzle(&args.input)?;
+
+    let lines = run(args.timeout, move || {
+        let mut session = Session::new(rows.clone(), cols.clone());
+
+        if session.solve().is_some() {
+            return None;
+        }
+
+        // the core can take a while, say what is known so far
+        if !quiet {
+            println!("UNSOLVABLE");
+        }
+
+        let lines = session.core()?.into_iter().map(|line| {
+            let clue = match line {
+                Line::Row(row) => &rows[row],
+                Line::Col(col) => &cols[col],
+            };
+
+            let nums: Vec<_> = clue.iter().map(u32::to_string).collect();
+            format!("{line}: {}\n", nums.join(" "))
+        });
+
+        Some(lines.collect::<String>())
+    })?;
+
+    let Some(lines) = lines else {
+        println!("SOLVABLE");
+        return Ok(());
+    };
+
+    print!("CONFLICTING CLUES:\n{lines}");
+
+    Err(Failure::Conflict)
+}
+
+// an image is scaled down to the puzzle size, a grid has to match it
+#[cfg(feature = "minisat")]
+fn read_picture(path: &Path, width: usize, height: usize) -> Result<Vec<Vec<bool>>, Failure> {
+    if is_image(path) {
+        let pixels = nonogram::image::load(path).map_err(Failure::Invalid)?;
+        let grid = nonogram::image::threshold(&pixels, 128);
+        return nonogram::image::downsample(&grid, width, height).map_err(Failure::Invalid);
+    }
+
+    let input = fs::read_to_string(path)
+        .map_err(|e| Failure::Invalid(format!("error reading {}: {e}", path.display())))?;
+
+    // the output of `solve` works as is
+    let input: Vec<_> = input
+        .lines()
+        .filter(|line| line.trim() != "SOLUTION:")
+        .collect();
+
+    nonogram::logic::parse_partial(&input.join("\n"))
+        .filter(|grid| grid.len() == height && grid.iter().all(|row| row.len() == width))
+        .filter(nonogram::logic::is_solved)
+        .map(|grid| nonogram::logic::unwrap(&grid))
+        .ok_or_else(|| {
+            Failure::Invalid(format!(
+                "error parsing picture, expected a {width}x{height} grid"
+            ))
+        })
+}
+
+#[cfg(feature = "tui")]
+fn play(args: PlayArgs) -> Outcome {
+    let (rows, cols) = read_puzzle(&args.input)?;
+
+    nonogram::tui::play(rows, cols, args.progress)
+        .map_err(|e| Failure::Invalid(format!("terminal error: {e}")))
+}
+
+#[cfg(feature = "server")]
+fn serve(args: ServeArgs, quiet: bool) -> Outcome {
+    use nonogram::server::{Config, Server};
+
+    let config = Config {
+        workers: args.workers.map_or_else(
+            || thread::available_parallelism().map_or(1, |n| n.get()),
+            |n| n as usize,
+        ),
+        timeout: args.timeout,
+    };
+
+    let server = Server::bind(&args.addr, config).map_err(Failure::Invalid)?;
+
+    if !quiet {
+        eprintln!("LISTENING:\nhttp://{}", server.addr());
+    }
+
+    server.run();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(args: &[&str]) -> Format {
+        match Cli::try_parse_from(args).unwrap().command {
+            Command::Solve(args) => args.format,
+            Command::Convert(args) => args.format,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn format_accepts_both_spellings() {
+        for command in ["solve", "convert"] {
+            assert!(format(&["nonogram", command, "--format", "json"]) == Format::Json);
+            assert!(format(&["nonogram", command, "--output-format", "json"]) == Format::Json);
+        }
     }
 }
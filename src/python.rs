@@ -0,0 +1,227 @@
+use std::time::Instant;
+
+use numpy::{PyArray1, PyArrayMethods};
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyDict, PyList},
+};
+
+use crate::{common, rate, report::Stats, solvers, Vec2D};
+
+#[pyclass(name = "Puzzle", frozen)]
+pub struct PyPuzzle {
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+}
+
+impl PyPuzzle {
+    fn checked(rows: Vec2D<u32>, cols: Vec2D<u32>) -> PyResult<Self> {
+        common::validate(&rows, &cols)
+            .map_err(|e| PyValueError::new_err(format!("incorrect nonogram: {e}")))?;
+
+        Ok(PyPuzzle { rows, cols })
+    }
+
+    fn clues(&self) -> (Vec2D<u32>, Vec2D<u32>) {
+        (self.rows.clone(), self.cols.clone())
+    }
+}
+
+#[pymethods]
+impl PyPuzzle {
+    #[new]
+    fn new(rows: Vec2D<u32>, cols: Vec2D<u32>) -> PyResult<Self> {
+        Self::checked(rows, cols)
+    }
+
+    /// Parses the text format.
+    #[staticmethod]
+    fn parse(text: String) -> PyResult<Self> {
+        let (rows, cols) =
+            common::parse(text).ok_or_else(|| PyValueError::new_err("error parsing nonogram"))?;
+
+        Self::checked(rows, cols)
+    }
+
+    #[staticmethod]
+    fn from_json(json: String) -> PyResult<Self> {
+        let (rows, cols) = common::parse_json(json)
+            .ok_or_else(|| PyValueError::new_err("error parsing nonogram"))?;
+
+        Self::checked(rows, cols)
+    }
+
+    fn to_json(&self) -> String {
+        common::serialize_json(&self.rows, &self.cols)
+    }
+
+    #[getter]
+    fn rows(&self) -> Vec2D<u32> {
+        self.rows.clone()
+    }
+
+    #[getter]
+    fn cols(&self) -> Vec2D<u32> {
+        self.cols.clone()
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.cols.len()
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Puzzle({}x{})", self.cols.len(), self.rows.len())
+    }
+}
+
+fn solver(name: Option<&str>) -> PyResult<(&str, solvers::Solve)> {
+    let name = name.unwrap_or(solvers::DEFAULT);
+    let solve = solvers::get(name).ok_or_else(|| {
+        let names: Vec<_> = solvers::names().collect();
+        PyValueError::new_err(format!(
+            "wrong solver name, expected one of: {}",
+            names.join(", ")
+        ))
+    })?;
+
+    Ok((name, solve))
+}
+
+fn grid<'py>(py: Python<'py>, grid: Vec2D<bool>, numpy: bool) -> PyResult<Bound<'py, PyAny>> {
+    if numpy {
+        let height = grid.len();
+        let width = grid.first().map_or(0, Vec::len);
+        let cells: Vec<_> = grid.into_iter().flatten().collect();
+
+        let array = PyArray1::from_vec(py, cells).reshape([height, width])?;
+
+        Ok(array.into_any())
+    } else {
+        Ok(PyList::new(py, grid)?.into_any())
+    }
+}
+
+/// Names of the solvers compiled into the module, default first.
+#[pyfunction(name = "solvers")]
+fn solver_names() -> Vec<&'static str> {
+    solvers::names().collect()
+}
+
+/// Solves a puzzle with the named solver, or the default one. Returns the grid
+/// as nested lists of bools, or as a 2D bool array with `numpy=True`, and
+/// `None` if the puzzle has no solution.
+#[pyfunction]
+#[pyo3(signature = (puzzle, solver = None, numpy = false))]
+fn solve<'py>(
+    py: Python<'py>,
+    puzzle: PyRef<'_, PyPuzzle>,
+    solver: Option<&str>,
+    numpy: bool,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let (_, solve) = self::solver(solver)?;
+
+    let (rows, cols) = puzzle.clues();
+    let solution = py.detach(|| solve(rows, cols));
+
+    solution.map(|s| grid(py, s, numpy)).transpose()
+}
+
+/// Like `solve`, but returns a dict with the solver, status, grid and timing.
+#[pyfunction]
+#[pyo3(signature = (puzzle, solver = None, numpy = false))]
+fn solve_stats<'py>(
+    py: Python<'py>,
+    puzzle: PyRef<'_, PyPuzzle>,
+    solver: Option<&str>,
+    numpy: bool,
+) -> PyResult<Bound<'py, PyDict>> {
+    let (name, solve) = self::solver(solver)?;
+
+    let (rows, cols) = puzzle.clues();
+
+    let (solution, elapsed) = py.detach(|| {
+        let start = Instant::now();
+        let solution = solve(rows, cols);
+        (solution, start.elapsed())
+    });
+
+    let stats = Stats::new(Default::default(), elapsed);
+
+    let dict = PyDict::new(py);
+    dict.set_item("solver", name)?;
+    dict.set_item("status", if solution.is_some() { "sat" } else { "unsat" })?;
+    dict.set_item("grid", solution.map(|s| grid(py, s, numpy)).transpose()?)?;
+    dict.set_item("solve_ms", stats.solve_ms)?;
+
+    Ok(dict)
+}
+
+/// Counts solutions, stopping at `limit`.
+#[pyfunction]
+#[pyo3(signature = (puzzle, limit = 2))]
+fn count(py: Python<'_>, puzzle: PyRef<'_, PyPuzzle>, limit: usize) -> usize {
+    let (rows, cols) = puzzle.clues();
+
+    py.detach(|| solvers::solutions(rows, cols, limit).len())
+}
+
+/// Every solution up to `limit`, in the same shape as `solve`.
+#[pyfunction]
+#[pyo3(signature = (puzzle, limit = 2, numpy = false))]
+fn solutions<'py>(
+    py: Python<'py>,
+    puzzle: PyRef<'_, PyPuzzle>,
+    limit: usize,
+    numpy: bool,
+) -> PyResult<Vec<Bound<'py, PyAny>>> {
+    let (rows, cols) = puzzle.clues();
+    let found = py.detach(|| solvers::solutions(rows, cols, limit));
+
+    found.into_iter().map(|s| grid(py, s, numpy)).collect()
+}
+
+/// Difficulty rating as a dict, or `None` if the puzzle has no solution.
+#[pyfunction(name = "rate")]
+fn rate_puzzle<'py>(
+    py: Python<'py>,
+    puzzle: PyRef<'_, PyPuzzle>,
+) -> PyResult<Option<Bound<'py, PyDict>>> {
+    let (rows, cols) = puzzle.clues();
+
+    let Some(rating) = py.detach(|| rate::rate(rows, cols)) else {
+        return Ok(None);
+    };
+
+    let tier = serde_json::to_value(rating.tier).expect("tier is always serializable");
+
+    let dict = PyDict::new(py);
+    dict.set_item("tier", tier.as_str())?;
+    dict.set_item("overlap", rating.overlap)?;
+    dict.set_item("line", rating.line)?;
+    dict.set_item("probe", rating.probe)?;
+    dict.set_item("guesses", rating.guesses)?;
+    dict.set_item("depth", rating.depth)?;
+    dict.set_item("score", rating.score)?;
+
+    Ok(Some(dict))
+}
+
+#[pymodule]
+fn nonogram(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPuzzle>()?;
+    m.add_function(wrap_pyfunction!(solver_names, m)?)?;
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_function(wrap_pyfunction!(solve_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(count, m)?)?;
+    m.add_function(wrap_pyfunction!(solutions, m)?)?;
+    m.add_function(wrap_pyfunction!(rate_puzzle, m)?)?;
+
+    Ok(())
+}
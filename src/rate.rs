@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    logic::{is_solved, Logic, Partial, Step, Tier},
+    Vec2D,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    pub tier: Tier,
+    pub overlap: usize,
+    pub line: usize,
+    pub probe: usize,
+    pub guesses: usize,
+    pub depth: usize,
+    pub score: f64,
+}
+
+pub fn rate(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Rating> {
+    let logic = Logic::new(rows, cols);
+
+    let mut grid = logic.empty();
+    let mut steps = Vec::new();
+
+    logic.deduce(&mut grid, &mut steps).ok()?;
+
+    let count = |tier| steps.iter().filter(|s: &&Step| s.tier == tier).count();
+
+    let mut rating = Rating {
+        tier: Tier::Overlap,
+        overlap: count(Tier::Overlap),
+        line: count(Tier::Line),
+        probe: count(Tier::Probe),
+        guesses: 0,
+        depth: 0,
+        score: 0.0,
+    };
+
+    if !is_solved(&grid) {
+        let mut search = Search::default();
+        search.run(&logic, grid, 1)?;
+
+        rating.guesses = search.guesses;
+        rating.depth = search.depth;
+    }
+
+    rating.tier = if rating.guesses > 0 {
+        Tier::Backtrack
+    } else if rating.probe > 0 {
+        Tier::Probe
+    } else if rating.line > 0 {
+        Tier::Line
+    } else {
+        Tier::Overlap
+    };
+
+    rating.score = rating.overlap as f64
+        + 2.0 * rating.line as f64
+        + 10.0 * rating.probe as f64
+        + 50.0 * rating.guesses as f64
+        + 100.0 * rating.depth as f64;
+
+    Some(rating)
+}
+
+#[derive(Default)]
+struct Search {
+    guesses: usize,
+    depth: usize,
+}
+
+impl Search {
+    fn run(&mut self, logic: &Logic, grid: Partial, depth: usize) -> Option<Partial> {
+        let (row, col) = (0..logic.height())
+            .flat_map(|row| (0..logic.width()).map(move |col| (row, col)))
+            .find(|&(row, col)| grid[row][col].is_none())?;
+
+        self.depth = self.depth.max(depth);
+
+        for value in [true, false] {
+            self.guesses += 1;
+
+            let mut trial = grid.clone();
+            trial[row][col] = Some(value);
+
+            let mut steps = Vec::new();
+
+            if logic.deduce(&mut trial, &mut steps).is_err() {
+                continue;
+            }
+
+            if is_solved(&trial) {
+                return Some(trial);
+            }
+
+            if let Some(solved) = self.run(logic, trial, depth + 1) {
+                return Some(solved);
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,200 @@
+use std::{fmt::Write, path::Path};
+
+use crate::{logic::Partial, Vec2D};
+
+const CELL: usize = 20;
+const UNKNOWN: &str = "#f0a030";
+
+pub fn svg(rows: &Vec2D<u32>, cols: &Vec2D<u32>, solution: Option<&Vec2D<bool>>) -> String {
+    draw(rows, cols, &|row, col| {
+        solution
+            .is_some_and(|solution| solution[row][col])
+            .then_some("black")
+    })
+}
+
+// like `svg`, with the unknown cells of a partial grid shaded
+pub fn svg_partial(rows: &Vec2D<u32>, cols: &Vec2D<u32>, grid: &Partial) -> String {
+    draw(rows, cols, &|row, col| match grid[row][col] {
+        Some(true) => Some("black"),
+        Some(false) => None,
+        None => Some(UNKNOWN),
+    })
+}
+
+// `fill` gives the colour of each cell, None leaves it blank
+fn draw(
+    rows: &Vec2D<u32>,
+    cols: &Vec2D<u32>,
+    fill: &dyn Fn(usize, usize) -> Option<&'static str>,
+) -> String {
+    let width = cols.len();
+    let height = rows.len();
+
+    // space for the longest clue on each side, at least one cell
+    let left = rows.iter().map(Vec::len).max().unwrap_or(0).max(1) * CELL;
+    let top = cols.iter().map(Vec::len).max().unwrap_or(0).max(1) * CELL;
+
+    let total_width = left + width * CELL + 1;
+    let total_height = top + height * CELL + 1;
+
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{total_width}" height="{total_height}" viewBox="0 0 {total_width} {total_height}">"#
+    );
+    let _ = writeln!(
+        out,
+        r#"<rect width="{total_width}" height="{total_height}" fill="white"/>"#
+    );
+
+    for i in 0..height {
+        for j in 0..width {
+            if let Some(color) = fill(i, j) {
+                let x = left + j * CELL;
+                let y = top + i * CELL;
+
+                let _ = writeln!(
+                    out,
+                    r#"<rect x="{x}" y="{y}" width="{CELL}" height="{CELL}" fill="{color}"/>"#
+                );
+            }
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        r#"<g font-family="sans-serif" font-size="{}" text-anchor="middle">"#,
+        CELL * 3 / 5
+    );
+
+    for (i, clue) in rows.iter().enumerate() {
+        let y = top + i * CELL + CELL * 7 / 10;
+
+        for (k, n) in clue.iter().rev().enumerate() {
+            let x = left - k * CELL - CELL / 2;
+            let _ = writeln!(out, r#"<text x="{x}" y="{y}">{n}</text>"#);
+        }
+    }
+
+    for (j, clue) in cols.iter().enumerate() {
+        let x = left + j * CELL + CELL / 2;
+
+        for (k, n) in clue.iter().rev().enumerate() {
+            let y = top - k * CELL - CELL * 3 / 10;
+            let _ = writeln!(out, r#"<text x="{x}" y="{y}">{n}</text>"#);
+        }
+    }
+
+    let _ = writeln!(out, "</g>");
+
+    // thick lines every 5 cells and on the border
+    let stroke = |i: usize, len: usize| {
+        if i.is_multiple_of(5) || i == len {
+            2
+        } else {
+            1
+        }
+    };
+
+    for i in 0..=height {
+        let y = top + i * CELL;
+        let _ = writeln!(
+            out,
+            r#"<line x1="0" y1="{y}" x2="{}" y2="{y}" stroke="black" stroke-width="{}"/>"#,
+            left + width * CELL,
+            stroke(i, height)
+        );
+    }
+
+    for j in 0..=width {
+        let x = left + j * CELL;
+        let _ = writeln!(
+            out,
+            r#"<line x1="{x}" y1="0" x2="{x}" y2="{}" stroke="black" stroke-width="{}"/>"#,
+            top + height * CELL,
+            stroke(j, width)
+        );
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+fn pixels(solution: &Vec2D<bool>, cell: usize) -> (usize, usize, Vec<bool>) {
+    let width = solution.first().map_or(0, Vec::len) * cell;
+    let height = solution.len() * cell;
+
+    let pixels = (0..height)
+        .flat_map(|y| (0..width).map(move |x| solution[y / cell][x / cell]))
+        .collect();
+
+    (width, height, pixels)
+}
+
+pub fn pbm(solution: &Vec2D<bool>, cell: usize) -> Vec<u8> {
+    let (width, height, pixels) = pixels(solution, cell);
+
+    let mut out = format!("P4\n{width} {height}\n").into_bytes();
+
+    if width == 0 {
+        return out;
+    }
+
+    for row in pixels.chunks(width) {
+        for byte in row.chunks(8) {
+            let bits = byte
+                .iter()
+                .enumerate()
+                .fold(0u8, |acc, (i, &p)| acc | ((p as u8) << (7 - i)));
+
+            out.push(bits);
+        }
+    }
+
+    out
+}
+
+fn gray(pixels: &[bool]) -> impl Iterator<Item = u8> + '_ {
+    pixels.iter().map(|&p| if p { 0 } else { 255 })
+}
+
+pub fn pgm(solution: &Vec2D<bool>, cell: usize) -> Vec<u8> {
+    let (width, height, pixels) = pixels(solution, cell);
+
+    let mut out = format!("P5\n{width} {height}\n255\n").into_bytes();
+    out.extend(gray(&pixels));
+    out
+}
+
+pub fn png(solution: &Vec2D<bool>, cell: usize) -> Result<Vec<u8>, String> {
+    let (width, height, pixels) = pixels(solution, cell);
+
+    let mut out = Vec::new();
+
+    let mut encoder = png::Encoder::new(&mut out, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+
+    writer
+        .write_image_data(&gray(&pixels).collect::<Vec<_>>())
+        .map_err(|e| e.to_string())?;
+
+    writer.finish().map_err(|e| e.to_string())?;
+
+    Ok(out)
+}
+
+pub fn write_image(path: &Path, solution: &Vec2D<bool>, cell: usize) -> Result<(), String> {
+    let data = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("pbm") => pbm(solution, cell),
+        Some("pgm") => pgm(solution, cell),
+        Some("png") => png(solution, cell)?,
+        _ => return Err(format!("unsupported image format: {}", path.display())),
+    };
+
+    std::fs::write(path, data).map_err(|e| format!("error writing {}: {e}", path.display()))
+}
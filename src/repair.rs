@@ -0,0 +1,189 @@
+use std::{collections::HashSet, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{common, logic::Line, session::Session, Vec2D};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    // one number one more or one less
+    Change,
+    // one block split in two over the same span
+    Split,
+    // two neighbouring blocks merged over the same span
+    Merge,
+    // a block of one taken out
+    Remove,
+    // a block of one put in an empty line
+    Add,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Repair {
+    pub line: Line,
+    pub kind: Kind,
+    pub before: Vec<u32>,
+    pub after: Vec<u32>,
+}
+
+// a line whose clue is not the one the picture gives
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mismatch {
+    pub line: Line,
+    pub clue: Vec<u32>,
+    pub expected: Vec<u32>,
+}
+
+// the number of solutions, up to 2, and single clue edits that leave the
+// puzzle with exactly one, at most `limit` of them; no edits if the puzzle is
+// unique already. For an unsolvable puzzle only the lines of a minimal
+// conflicting set are tried, since any fix has to touch one of them
+pub fn repair(rows: Vec2D<u32>, cols: Vec2D<u32>, limit: usize) -> (usize, Vec<Repair>) {
+    let mut session = Session::new(rows, cols);
+
+    let count = session.solutions(2).len();
+
+    let lines = match count {
+        0 => session.core().unwrap_or_default(),
+        1 => return (count, Vec::new()),
+        _ => session.lines().collect(),
+    };
+
+    let mut repairs = Vec::new();
+
+    for line in lines {
+        let before = session.clue(line).to_vec();
+        let len = match line {
+            Line::Row(_) => session.width(),
+            Line::Col(_) => session.height(),
+        };
+
+        for (kind, after) in edits(&before, len) {
+            if repairs.len() >= limit {
+                return (count, repairs);
+            }
+
+            let found = session
+                .solutions_with(line, &after, 2)
+                .expect("edits fit the line");
+
+            if found.len() == 1 {
+                repairs.push(Repair {
+                    line,
+                    kind,
+                    before: before.clone(),
+                    after,
+                });
+            }
+        }
+    }
+
+    (count, repairs)
+}
+
+fn edits(clue: &[u32], len: usize) -> Vec<(Kind, Vec<u32>)> {
+    let mut edits = Vec::new();
+
+    // the clue with `replaced` numbers from `i` on swapped for `nums`
+    let with = |i: usize, replaced: usize, nums: &[u32]| {
+        let mut clue = clue.to_vec();
+        clue.splice(i..i + replaced, nums.iter().copied());
+        clue
+    };
+
+    if clue.is_empty() {
+        edits.push((Kind::Add, vec![1]));
+    }
+
+    for (i, &n) in clue.iter().enumerate() {
+        if n > 1 {
+            edits.push((Kind::Change, with(i, 1, &[n - 1])));
+        } else {
+            edits.push((Kind::Remove, with(i, 1, &[])));
+        }
+
+        edits.push((Kind::Change, with(i, 1, &[n + 1])));
+
+        for a in 1..n.saturating_sub(1) {
+            edits.push((Kind::Split, with(i, 1, &[a, n - 1 - a])));
+        }
+
+        if let Some(&m) = clue.get(i + 1) {
+            edits.push((Kind::Merge, with(i, 2, &[n + 1 + m])));
+        }
+    }
+
+    // removing any block of a run of ones gives the same clue
+    let mut seen = HashSet::new();
+    edits.retain(|(_, clue)| common::clue_fits(clue, len) && seen.insert(clue.clone()));
+
+    edits
+}
+
+// the lines whose clues differ from the ones read off the intended picture
+pub fn mismatches(rows: &Vec2D<u32>, cols: &Vec2D<u32>, picture: &Vec2D<bool>) -> Vec<Mismatch> {
+    let (expected_rows, expected_cols) = common::clues(picture);
+
+    let rows = rows
+        .iter()
+        .zip(expected_rows)
+        .enumerate()
+        .map(|(i, (clue, expected))| (Line::Row(i), clue, expected));
+
+    let cols = cols
+        .iter()
+        .zip(expected_cols)
+        .enumerate()
+        .map(|(i, (clue, expected))| (Line::Col(i), clue, expected));
+
+    rows.chain(cols)
+        .filter(|(_, clue, expected)| *clue != expected)
+        .map(|(line, clue, expected)| Mismatch {
+            line,
+            clue: clue.clone(),
+            expected,
+        })
+        .collect()
+}
+
+fn format_clue(clue: &[u32]) -> String {
+    if clue.is_empty() {
+        return "empty".to_string();
+    }
+
+    let nums: Vec<_> = clue.iter().map(u32::to_string).collect();
+    nums.join(" ")
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            Kind::Change => "change",
+            Kind::Split => "split",
+            Kind::Merge => "merge",
+            Kind::Remove => "remove",
+            Kind::Add => "add",
+        };
+
+        write!(
+            f,
+            "{}: {} -> {} ({kind})",
+            self.line,
+            format_clue(&self.before),
+            format_clue(&self.after),
+        )
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}, picture has {}",
+            self.line,
+            format_clue(&self.clue),
+            format_clue(&self.expected),
+        )
+    }
+}
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Vec2D;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Sat,
+    Unsat,
+    Timeout,
+    Invalid,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    pub parse_ms: f64,
+    pub solve_ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub solver: String,
+    pub status: Status,
+    pub width: usize,
+    pub height: usize,
+    pub grid: Option<Vec2D<bool>>,
+    pub stats: Stats,
+}
+
+impl Report {
+    pub fn new(solver: &str, width: usize, height: usize, solution: Option<Vec2D<bool>>) -> Self {
+        Report {
+            solver: solver.to_string(),
+            status: if solution.is_some() {
+                Status::Sat
+            } else {
+                Status::Unsat
+            },
+            width,
+            height,
+            grid: solution,
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("report is always serializable")
+    }
+}
+
+impl Stats {
+    pub fn new(parse: Duration, solve: Duration) -> Self {
+        let millis = |d: Duration| d.as_secs_f64() * 1000.0;
+
+        Stats {
+            parse_ms: millis(parse),
+            solve_ms: millis(solve),
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::{iter::once, ops::Not};
+
+use crate::Vec2D;
+
+// the solver interface the encodings are written against, so that every
+// encoding runs on every backend
+pub(crate) trait SolverExt {
+    type Lit: Copy + Not<Output = Self::Lit>;
+
+    // false if the solver is spent after one call to `sat`
+    const INCREMENTAL: bool;
+
+    fn new() -> Self;
+    fn var(&mut self) -> Self::Lit;
+    fn add(&mut self, lits: &[Self::Lit]);
+
+    // the values of `lits` in a model, None if there is none
+    fn sat(&mut self, lits: &[Self::Lit]) -> Option<Vec<bool>>;
+
+    fn add1(&mut self, a: Self::Lit) {
+        self.add(&[a]);
+    }
+
+    fn add2(&mut self, a: Self::Lit, b: Self::Lit) {
+        self.add(&[a, b]);
+    }
+
+    fn and_literal(&mut self, lits: &[Self::Lit]) -> Self::Lit {
+        let res = self.var();
+
+        for &lit in lits {
+            self.add2(!res, lit);
+        }
+
+        self.add(
+            &lits
+                .iter()
+                .map(|&lit| !lit)
+                .chain(once(res))
+                .collect::<Vec<_>>(),
+        );
+
+        res
+    }
+}
+
+#[cfg(feature = "kissat")]
+impl SolverExt for kissat::Solver {
+    type Lit = kissat::Var;
+
+    const INCREMENTAL: bool = false;
+
+    fn new() -> Self {
+        kissat::Solver::new()
+    }
+
+    fn var(&mut self) -> Self::Lit {
+        kissat::Solver::var(self)
+    }
+
+    fn add(&mut self, lits: &[Self::Lit]) {
+        kissat::Solver::add(self, lits);
+    }
+
+    // kissat solves only once, a fresh solver is left in its place
+    fn sat(&mut self, lits: &[Self::Lit]) -> Option<Vec<bool>> {
+        let solution = std::mem::take(self).sat()?;
+
+        Some(lits.iter().map(|&lit| solution.get(lit).unwrap()).collect())
+    }
+}
+
+#[cfg(feature = "minisat")]
+impl SolverExt for minisat::Solver {
+    type Lit = minisat::Bool;
+
+    const INCREMENTAL: bool = true;
+
+    fn new() -> Self {
+        minisat::Solver::new()
+    }
+
+    fn var(&mut self) -> Self::Lit {
+        self.new_lit()
+    }
+
+    fn add(&mut self, lits: &[Self::Lit]) {
+        self.add_clause(lits.iter().copied());
+    }
+
+    fn sat(&mut self, lits: &[Self::Lit]) -> Option<Vec<bool>> {
+        let model = self.solve().ok()?;
+
+        Some(lits.iter().map(|lit| model.value(lit)).collect())
+    }
+}
+
+#[cfg(feature = "varisat")]
+impl SolverExt for varisat::Solver<'static> {
+    type Lit = varisat::Lit;
+
+    const INCREMENTAL: bool = true;
+
+    fn new() -> Self {
+        varisat::Solver::new()
+    }
+
+    fn var(&mut self) -> Self::Lit {
+        varisat::ExtendFormula::new_lit(self)
+    }
+
+    fn add(&mut self, lits: &[Self::Lit]) {
+        varisat::ExtendFormula::add_clause(self, lits);
+    }
+
+    fn sat(&mut self, lits: &[Self::Lit]) -> Option<Vec<bool>> {
+        if !self.solve().ok()? {
+            return None;
+        }
+
+        let mut values = Vec::new();
+
+        for lit in self.model()? {
+            let index = lit.var().index();
+
+            if index >= values.len() {
+                values.resize(index + 1, false);
+            }
+
+            values[index] = lit.is_positive();
+        }
+
+        let value = |lit: &varisat::Lit| {
+            let value = values.get(lit.var().index()).copied().unwrap_or(false);
+            value == lit.is_positive()
+        };
+
+        Some(lits.iter().map(value).collect())
+    }
+}
+
+pub(crate) fn cells<S: SolverExt>(s: &mut S, width: usize, height: usize) -> Vec2D<S::Lit> {
+    (0..height)
+        .map(|_| (0..width).map(|_| s.var()).collect())
+        .collect()
+}
+
+pub(crate) fn columns<T: Copy>(grid: &Vec2D<T>, width: usize) -> Vec2D<T> {
+    (0..width)
+        .map(|col| grid.iter().map(|row| row[col]).collect())
+        .collect()
+}
+
+pub(crate) fn model<S: SolverExt>(s: &mut S, cells: &Vec2D<S::Lit>) -> Option<Vec2D<bool>> {
+    let flat: Vec<_> = cells.iter().flatten().copied().collect();
+    let mut values = s.sat(&flat)?.into_iter();
+
+    let answer = cells
+        .iter()
+        .map(|row| row.iter().map(|_| values.next().unwrap()).collect())
+        .collect();
+
+    Some(answer)
+}
+
+// the clause that rules out exactly this grid
+pub(crate) fn blocking<S: SolverExt>(cells: &Vec2D<S::Lit>, answer: &Vec2D<bool>) -> Vec<S::Lit> {
+    std::iter::zip(cells.iter().flatten(), answer.iter().flatten())
+        .map(|(&lit, &value)| if value { !lit } else { lit })
+        .collect()
+}
+
+// encodes every row and column with `add_condition`
+pub(crate) fn encode_lines<S: SolverExt>(
+    s: &mut S,
+    rows: &Vec2D<u32>,
+    cols: &Vec2D<u32>,
+    add_condition: impl Fn(&mut S, &[S::Lit], &[u32], u32),
+) -> Vec2D<S::Lit> {
+    let width = cols.len();
+    let height = rows.len();
+
+    let cells = cells(s, width, height);
+
+    for (row, lits) in std::iter::zip(rows, &cells) {
+        add_condition(s, lits, row, width as u32);
+    }
+
+    for (col, lits) in std::iter::zip(cols, &columns(&cells, width)) {
+        add_condition(s, lits, col, height as u32);
+    }
+
+    cells
+}
+
+pub(crate) fn solve_lines<S: SolverExt>(
+    rows: &Vec2D<u32>,
+    cols: &Vec2D<u32>,
+    add_condition: fn(&mut S, &[S::Lit], &[u32], u32),
+) -> Option<Vec2D<bool>> {
+    let mut s = S::new();
+    let cells = encode_lines(&mut s, rows, cols, add_condition);
+
+    model(&mut s, &cells)
+}
+
+// up to `limit` distinct solutions of what `encode` adds to a fresh solver.
+// Incremental solvers keep going with each solution blocked, the others
+// start over with every solution found so far blocked
+pub(crate) fn solutions<S: SolverExt>(
+    limit: usize,
+    encode: impl Fn(&mut S) -> Vec2D<S::Lit>,
+) -> Vec<Vec2D<bool>> {
+    let mut s = S::new();
+    let mut cells = encode(&mut s);
+
+    let mut found = Vec::new();
+
+    while found.len() < limit {
+        let Some(answer) = model(&mut s, &cells) else {
+            break;
+        };
+
+        found.push(answer);
+
+        if !S::INCREMENTAL {
+            s = S::new();
+            cells = encode(&mut s);
+
+            for answer in &found {
+                s.add(&blocking::<S>(&cells, answer));
+            }
+        } else {
+            s.add(&blocking::<S>(&cells, &found[found.len() - 1]));
+        }
+    }
+
+    found
+}
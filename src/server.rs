@@ -0,0 +1,509 @@
+use std::{
+    io::Read,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::{
+    common, hint,
+    logic::Partial,
+    render,
+    report::{Report, Stats, Status},
+    solvers, Vec2D,
+};
+
+const MAX_BODY: u64 = 1 << 20;
+const MAX_COUNT: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub workers: usize,
+    pub timeout: Duration,
+}
+
+pub struct Server {
+    http: tiny_http::Server,
+    config: Config,
+    // requests waiting on a solver, at most `workers`
+    solving: Mutex<usize>,
+}
+
+// a request counted in `solving` until it is dropped
+struct Slot<'a>(&'a Mutex<usize>);
+
+// why a solver produced no result
+enum Abort {
+    Busy,
+    Timeout,
+    Panic,
+}
+
+#[derive(Deserialize)]
+struct SolveRequest {
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    solver: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CountRequest {
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct HintRequest {
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    grid: Partial,
+}
+
+#[derive(Deserialize)]
+struct RenderRequest {
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    solver: Option<String>,
+    #[serde(default)]
+    blank: bool,
+}
+
+#[derive(Serialize)]
+struct Error {
+    error: String,
+}
+
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Reply {
+    fn json(status: u16, body: impl Serialize) -> Self {
+        Reply {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_string(&body).expect("reply is always serializable"),
+        }
+    }
+
+    fn error(status: u16, error: impl Into<String>) -> Self {
+        Reply::json(
+            status,
+            Error {
+                error: error.into(),
+            },
+        )
+    }
+}
+
+impl Server {
+    pub fn bind(addr: &str, config: Config) -> Result<Self, String> {
+        let http =
+            tiny_http::Server::http(addr).map_err(|e| format!("error binding {addr}: {e}"))?;
+
+        Ok(Server {
+            http,
+            config,
+            solving: Mutex::new(0),
+        })
+    }
+
+    pub fn addr(&self) -> String {
+        self.http.server_addr().to_string()
+    }
+
+    // serves each request on its own thread until the process exits; no
+    // more than `workers` of them solve at once, the rest are turned away
+    pub fn run(&self) {
+        thread::scope(|scope| {
+            for request in self.http.incoming_requests() {
+                scope.spawn(|| self.handle(request));
+            }
+        });
+    }
+
+    fn handle(&self, mut request: Request) {
+        let mut body = String::new();
+
+        let read = request.as_reader().take(MAX_BODY).read_to_string(&mut body);
+
+        let reply = match read {
+            Ok(_) => self.route(request.method(), request.url(), body),
+            Err(e) => Reply::error(400, format!("error reading request: {e}")),
+        };
+
+        let header = Header::from_bytes("Content-Type", reply.content_type)
+            .expect("content type is a valid header");
+
+        let response = Response::from_string(reply.body)
+            .with_status_code(reply.status)
+            .with_header(header);
+
+        // the client may have gone away, nothing to do about it
+        let _ = request.respond(response);
+    }
+
+    fn route(&self, method: &Method, url: &str, body: String) -> Reply {
+        let path = url.split('?').next().unwrap_or_default();
+
+        match (method, path) {
+            (Method::Get, "/health") => Reply::json(
+                200,
+                json!({ "status": "ok", "solvers": solvers::names().collect::<Vec<_>>() }),
+            ),
+            (Method::Post, "/solve") => self.solve(body),
+            (Method::Post, "/validate") => validate(body),
+            (Method::Post, "/count") => self.count(body),
+            (Method::Post, "/hint") => self.hint(body),
+            (Method::Post, "/render") => self.render(body),
+            (_, "/health") => Reply::error(405, "expected GET"),
+            (_, "/solve" | "/validate" | "/count" | "/hint" | "/render") => {
+                Reply::error(405, "expected POST")
+            }
+            _ => Reply::error(404, format!("no such endpoint: {path}")),
+        }
+    }
+
+    // runs `f` on its own thread and sets the cancel flag once the request
+    // times out. Only cancellable solvers check it; the SAT solvers cannot be
+    // interrupted, so they finish in the background without counting against
+    // `workers`, or a few stuck solvers would lock out every later request
+    fn run_cancellable<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&AtomicBool) -> T + Send + 'static,
+    ) -> Result<T, Abort> {
+        let Some(_slot) = self.slot() else {
+            return Err(Abort::Busy);
+        };
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+
+        let flag = cancel.clone();
+        thread::spawn(move || {
+            let _ = tx.send(f(&flag));
+        });
+
+        rx.recv_timeout(self.config.timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => {
+                cancel.store(true, Ordering::Relaxed);
+                Abort::Timeout
+            }
+            RecvTimeoutError::Disconnected => Abort::Panic,
+        })
+    }
+
+    fn slot(&self) -> Option<Slot<'_>> {
+        let mut solving = self.solving.lock().unwrap();
+
+        if *solving >= self.config.workers.max(1) {
+            return None;
+        }
+
+        *solving += 1;
+        Some(Slot(&self.solving))
+    }
+
+    fn solve(&self, body: String) -> Reply {
+        let request: SolveRequest = match parse(&body) {
+            Ok(request) => request,
+            Err(reply) => return reply,
+        };
+
+        if let Err(reply) = check(&request.rows, &request.cols) {
+            return reply;
+        }
+
+        let name = request
+            .solver
+            .unwrap_or_else(|| solvers::DEFAULT.to_string());
+
+        if solvers::get(&name).is_none() {
+            return Reply::error(400, "wrong solver name");
+        }
+
+        let (width, height) = (request.cols.len(), request.rows.len());
+        let (rows, cols) = (request.rows, request.cols);
+
+        let start = Instant::now();
+        let solver = name.clone();
+        let result = self.run_cancellable(move |cancel| solve(&solver, rows, cols, cancel));
+        let elapsed = start.elapsed();
+
+        let (status, solution) = match result {
+            Ok(solution) => (200, solution),
+            Err(Abort::Timeout) => (504, None),
+            Err(abort) => return abort.reply(),
+        };
+
+        let mut report = Report::new(&name, width, height, solution);
+        report.stats = Stats::new(Duration::ZERO, elapsed);
+
+        if status == 504 {
+            report.status = Status::Timeout;
+        }
+
+        Reply::json(status, report)
+    }
+
+    fn count(&self, body: String) -> Reply {
+        let request: CountRequest = match parse(&body) {
+            Ok(request) => request,
+            Err(reply) => return reply,
+        };
+
+        if let Err(reply) = check(&request.rows, &request.cols) {
+            return reply;
+        }
+
+        let limit = request.limit.unwrap_or(2).min(MAX_COUNT);
+        let (rows, cols) = (request.rows, request.cols);
+
+        match self.run_cancellable(move |cancel| solutions(rows, cols, limit, cancel).len()) {
+            Ok(count) => Reply::json(200, json!({ "count": count, "limit": limit })),
+            Err(Abort::Timeout) => Reply::json(504, json!({ "status": "timeout", "limit": limit })),
+            Err(abort) => abort.reply(),
+        }
+    }
+
+    fn hint(&self, body: String) -> Reply {
+        let request: HintRequest = match parse(&body) {
+            Ok(request) => request,
+            Err(reply) => return reply,
+        };
+
+        if let Err(reply) = check(&request.rows, &request.cols) {
+            return reply;
+        }
+
+        let (width, height) = (request.cols.len(), request.rows.len());
+
+        if request.grid.len() != height || request.grid.iter().any(|row| row.len() != width) {
+            return Reply::error(400, "grid does not match the puzzle size");
+        }
+
+        let (rows, cols, grid) = (request.rows, request.cols, request.grid);
+
+        match self.run_cancellable(move |_| hint::hint(rows, cols, &grid)) {
+            Ok(Some(hint)) => Reply::json(200, hint),
+            Ok(None) => Reply::error(422, "puzzle is unsolvable"),
+            Err(abort) => abort.reply(),
+        }
+    }
+
+    fn render(&self, body: String) -> Reply {
+        let request: RenderRequest = match parse(&body) {
+            Ok(request) => request,
+            Err(reply) => return reply,
+        };
+
+        if let Err(reply) = check(&request.rows, &request.cols) {
+            return reply;
+        }
+
+        let svg = |body| Reply {
+            status: 200,
+            content_type: "image/svg+xml",
+            body,
+        };
+
+        if request.blank {
+            return svg(render::svg(&request.rows, &request.cols, None));
+        }
+
+        let name = request
+            .solver
+            .unwrap_or_else(|| solvers::DEFAULT.to_string());
+
+        if solvers::get(&name).is_none() {
+            return Reply::error(400, "wrong solver name");
+        }
+
+        let (rows, cols) = (request.rows.clone(), request.cols.clone());
+
+        match self.run_cancellable(move |cancel| solve(&name, rows, cols, cancel)) {
+            Ok(Some(solution)) => svg(render::svg(&request.rows, &request.cols, Some(&solution))),
+            Ok(None) => Reply::error(422, "puzzle is unsolvable"),
+            Err(abort) => abort.reply(),
+        }
+    }
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        *self.0.lock().unwrap() -= 1;
+    }
+}
+
+impl Abort {
+    fn reply(self) -> Reply {
+        match self {
+            Abort::Busy => Reply::error(503, "too many solvers running, try again later"),
+            Abort::Timeout => Reply::json(504, json!({ "status": "timeout" })),
+            Abort::Panic => Reply::error(500, "solver crashed"),
+        }
+    }
+}
+
+fn validate(body: String) -> Reply {
+    let puzzle: common::Puzzle = match parse(&body) {
+        Ok(puzzle) => puzzle,
+        Err(reply) => return reply,
+    };
+
+    match common::validate(&puzzle.rows, &puzzle.cols) {
+        Ok(()) => Reply::json(200, json!({ "valid": true })),
+        Err(e) => Reply::json(200, json!({ "valid": false, "error": e })),
+    }
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(body: &str) -> Result<T, Reply> {
+    serde_json::from_str(body).map_err(|e| Reply::error(400, format!("error parsing request: {e}")))
+}
+
+fn check(rows: &Vec2D<u32>, cols: &Vec2D<u32>) -> Result<(), Reply> {
+    common::validate(rows, cols).map_err(|e| Reply::error(400, format!("incorrect nonogram: {e}")))
+}
+
+fn solve(
+    name: &str,
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    cancel: &AtomicBool,
+) -> Option<Vec2D<bool>> {
+    match solvers::get_cancellable(name) {
+        Some(solve) => solve(rows, cols, cancel),
+        None => solvers::get(name)?(rows, cols),
+    }
+}
+
+#[cfg(feature = "backtrack")]
+fn solutions(
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    limit: usize,
+    cancel: &AtomicBool,
+) -> Vec<Vec2D<bool>> {
+    crate::solver_backtrack::solutions_cancellable(rows, cols, limit, cancel)
+}
+
+#[cfg(not(feature = "backtrack"))]
+fn solutions(
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    limit: usize,
+    _cancel: &AtomicBool,
+) -> Vec<Vec2D<bool>> {
+    solvers::solutions(rows, cols, limit)
+}
+
+#[cfg(all(test, feature = "backtrack"))]
+mod tests {
+    use std::{
+        fs,
+        io::Write,
+        net::TcpStream,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+
+    fn start(workers: usize, timeout: Duration) -> Arc<Server> {
+        let config = Config { workers, timeout };
+        let server = Arc::new(Server::bind("127.0.0.1:0", config).unwrap());
+
+        let serving = server.clone();
+        thread::spawn(move || serving.run());
+
+        server
+    }
+
+    fn puzzle(name: &str, solver: &str) -> String {
+        let path = format!("{}/nonograms/{name}", env!("CARGO_MANIFEST_DIR"));
+        let (rows, cols) = common::parse(fs::read_to_string(path).unwrap()).unwrap();
+
+        json!({ "rows": rows, "cols": cols, "solver": solver }).to_string()
+    }
+
+    fn post(server: &Server, path: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+
+        write!(
+            stream,
+            "POST {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let status = response.split(' ').nth(1).unwrap().parse().unwrap();
+        let body = response.split("\r\n\r\n").nth(1).unwrap_or_default();
+
+        (status, body.to_string())
+    }
+
+    #[test]
+    fn slow_solve_times_out() {
+        let server = start(1, Duration::from_millis(300));
+
+        let start = Instant::now();
+        let (status, body) = post(&server, "/solve", &puzzle("gigantic", "backtrack"));
+
+        assert_eq!(status, 504);
+        assert!(body.contains(r#""status":"timeout""#));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn busy_server_turns_requests_away() {
+        let server = start(1, Duration::from_secs(3));
+
+        let slow = {
+            let server = server.clone();
+            thread::spawn(move || post(&server, "/solve", &puzzle("gigantic", "backtrack")).0)
+        };
+
+        // let the slow request take the only worker
+        thread::sleep(Duration::from_millis(500));
+
+        assert_eq!(
+            post(&server, "/solve", &puzzle("example", "backtrack")).0,
+            503
+        );
+        assert_eq!(slow.join().unwrap(), 504);
+        assert_eq!(
+            post(&server, "/solve", &puzzle("example", "backtrack")).0,
+            200
+        );
+    }
+
+    #[test]
+    fn stuck_solvers_do_not_lock_out_later_requests() {
+        let server = start(1, Duration::from_millis(100));
+
+        // solvers that ignore the cancel flag, like the SAT backends
+        for _ in 0..3 {
+            let stuck = server.run_cancellable(|_| thread::sleep(Duration::from_secs(3600)));
+            assert!(matches!(stuck, Err(Abort::Timeout)));
+        }
+
+        assert_eq!(
+            post(&server, "/solve", &puzzle("example", "backtrack")).0,
+            200
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use minisat::{Bool, Solver};
+
+use crate::{
+    common::clue_fits,
+    explain::Cell,
+    logic::{Line, Logic, Partial},
+    solver_automaton::add_condition,
+    Vec2D,
+};
+
+// a solver that stays alive across clue edits: every line is encoded as an
+// automaton whose clauses hang off an activation literal, so a line can be
+// swapped out without re-encoding the rest and the learned clauses carry over
+// to the next solve
+pub struct Session {
+    solver: Solver,
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    cells: Vec2D<Bool>,
+    // rows first, then columns
+    active: Vec<Bool>,
+}
+
+impl Session {
+    pub fn new(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Self {
+        let mut solver = Solver::new();
+
+        let cells: Vec2D<_> = (0..rows.len())
+            .map(|_| (0..cols.len()).map(|_| solver.new_lit()).collect())
+            .collect();
+
+        let mut session = Session {
+            solver,
+            rows,
+            cols,
+            cells,
+            active: Vec::new(),
+        };
+
+        let lines: Vec<_> = session.lines().collect();
+
+        for line in lines {
+            let clue = session.clue(line).to_vec();
+            let active = session.encode(line, &clue);
+            session.active.push(active);
+        }
+
+        session
+    }
+
+    pub fn width(&self) -> usize {
+        self.cols.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn rows(&self) -> &Vec2D<u32> {
+        &self.rows
+    }
+
+    pub fn cols(&self) -> &Vec2D<u32> {
+        &self.cols
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = Line> {
+        (0..self.height())
+            .map(Line::Row)
+            .chain((0..self.width()).map(Line::Col))
+    }
+
+    pub fn clue(&self, line: Line) -> &[u32] {
+        match line {
+            Line::Row(row) => &self.rows[row],
+            Line::Col(col) => &self.cols[col],
+        }
+    }
+
+    // replaces the clue of one line; the old clause group is switched off for
+    // good and a new one is added in its place
+    pub fn set_clue(&mut self, line: Line, clue: Vec<u32>) -> Result<(), String> {
+        let index = self.check(line, &clue)?;
+
+        let old = self.active[index];
+        self.solver.add_clause([!old]);
+        self.active[index] = self.encode(line, &clue);
+
+        match line {
+            Line::Row(row) => self.rows[row] = clue,
+            Line::Col(col) => self.cols[col] = clue,
+        }
+
+        Ok(())
+    }
+
+    // up to `limit` solutions with `clue` in place of the clue of `line`,
+    // leaving the session as it was; the trial clue and its blocking clauses
+    // hang off one literal that is switched off afterwards
+    pub fn solutions_with(
+        &mut self,
+        line: Line,
+        clue: &[u32],
+        limit: usize,
+    ) -> Result<Vec<Vec2D<bool>>, String> {
+        let index = self.check(line, clue)?;
+
+        let trial = self.encode(line, clue);
+
+        let mut assumptions = self.active.clone();
+        assumptions[index] = trial;
+
+        let found = self.solutions_under(&assumptions, trial, limit);
+        self.solver.add_clause([!trial]);
+
+        Ok(found)
+    }
+
+    pub fn solve(&mut self) -> Option<Vec2D<bool>> {
+        let active = self.active.clone();
+        self.solve_under(&active)
+    }
+
+    // up to `limit` distinct solutions; the blocking clauses are guarded by
+    // their own literal, which is switched off afterwards
+    pub fn solutions(&mut self, limit: usize) -> Vec<Vec2D<bool>> {
+        let block = self.solver.new_lit();
+
+        let mut assumptions = self.active.clone();
+        assumptions.push(block);
+
+        let found = self.solutions_under(&assumptions, block, limit);
+        self.solver.add_clause([!block]);
+
+        found
+    }
+
+    // a solution that agrees with the known cells, which are assumed rather
+    // than added, so the session can be asked again with others
+    pub fn solve_partial(&mut self, known: &Partial) -> Option<Vec2D<bool>> {
+        let fixed: Vec<_> = std::iter::zip(self.cells.iter().flatten(), known.iter().flatten())
+            .filter_map(|(&lit, &value)| Some(if value? { lit } else { !lit }))
+            .collect();
+
+        let assumptions: Vec<_> = self.active.iter().copied().chain(fixed).collect();
+
+        self.solve_under(&assumptions)
+    }
+
+    // solves with only the given lines enforced
+    pub fn solve_lines(&mut self, lines: &[Line]) -> Option<Vec2D<bool>> {
+        let assumptions: Vec<_> = lines
+            .iter()
+            .filter_map(|&line| self.index(line))
+            .map(|index| self.active[index])
+            .collect();
+
+        self.solve_under(&assumptions)
+    }
+
+    // a minimal set of lines that has no solution on its own, or None if the
+    // puzzle is solvable. minisat does not report which assumptions failed, so
+    // the lines line solving needs to reach a contradiction stand in for them
+    // where it finds one. Each line is then dropped in turn and stays out if
+    // the rest is still unsolvable, which line solving often shows without
+    // asking the solver, and its own conflict shrinks the rest further
+    pub fn core(&mut self) -> Option<Vec<Line>> {
+        let logic = Logic::new(self.rows.clone(), self.cols.clone());
+        let all: Vec<_> = self.lines().collect();
+
+        let mut core = match logic.conflict(&all) {
+            Some(conflict) => conflict,
+            None if self.solve_lines(&all).is_some() => return None,
+            None => all,
+        };
+
+        // lines without which the core has a solution
+        let mut needed = Vec::new();
+
+        while let Some(&line) = core.iter().find(|line| !needed.contains(*line)) {
+            let rest: Vec<_> = core.iter().copied().filter(|&l| l != line).collect();
+
+            if let Some(conflict) = logic.conflict(&rest) {
+                core = conflict;
+            } else if self.solve_lines(&rest).is_none() {
+                core = rest;
+            } else {
+                needed.push(line);
+            }
+        }
+
+        core.sort_by_key(|&line| self.index(line));
+
+        Some(core)
+    }
+
+    // the value every solution agrees on for each cell, None where they differ;
+    // None if there is no solution at all. Each call asks for a model that
+    // flips at least one cell still thought fixed and drops every cell it
+    // flips, so the last, unsatisfiable call settles all the rest at once
+    pub fn backbone(&mut self) -> Option<Partial> {
+        let first = self.solve()?;
+
+        let mut backbone: Partial = first
+            .iter()
+            .map(|row| row.iter().map(|&cell| Some(cell)).collect())
+            .collect();
+
+        loop {
+            let flip = self.solver.new_lit();
+
+            let flipped: Vec<_> =
+                std::iter::zip(self.cells.iter().flatten(), backbone.iter().flatten())
+                    .filter_map(|(&lit, &value)| Some(if value? { !lit } else { lit }))
+                    .chain([!flip])
+                    .collect();
+
+            self.solver.add_clause(flipped);
+
+            let mut assumptions = self.active.clone();
+            assumptions.push(flip);
+
+            let other = self.solve_under(&assumptions);
+            self.solver.add_clause([!flip]);
+
+            let Some(other) = other else {
+                break;
+            };
+
+            for (cells, others) in std::iter::zip(&mut backbone, &other) {
+                for (cell, &other) in std::iter::zip(cells, others) {
+                    if cell.is_some_and(|value| value != other) {
+                        *cell = None;
+                    }
+                }
+            }
+        }
+
+        Some(backbone)
+    }
+
+    // cells of one solution that, once given, leave it the only solution;
+    // picked one at a time where another solution still differs from it
+    pub fn givens(&mut self) -> Option<Vec<Cell>> {
+        let mut assumptions = self.active.clone();
+
+        let solution = self.solve_under(&assumptions)?;
+
+        let cell_lit = |lit: Bool, value: bool| if value { lit } else { !lit };
+
+        // any other solution has to differ in some cell
+        let block = self.solver.new_lit();
+        let blocking: Vec<_> =
+            std::iter::zip(self.cells.iter().flatten(), solution.iter().flatten())
+                .map(|(&lit, &value)| cell_lit(!lit, value))
+                .chain([!block])
+                .collect();
+
+        self.solver.add_clause(blocking);
+        assumptions.push(block);
+
+        let mut givens = Vec::new();
+
+        while let Some(other) = self.solve_under(&assumptions) {
+            let (row, col) = (0..self.height())
+                .flat_map(|row| (0..self.width()).map(move |col| (row, col)))
+                .find(|&(row, col)| other[row][col] != solution[row][col])
+                .expect("another solution differs in some cell");
+
+            let filled = solution[row][col];
+
+            assumptions.push(cell_lit(self.cells[row][col], filled));
+            givens.push(Cell { row, col, filled });
+        }
+
+        self.solver.add_clause([!block]);
+
+        Some(givens)
+    }
+
+    // every solution found is blocked for as long as `guard` holds
+    fn solutions_under(
+        &mut self,
+        assumptions: &[Bool],
+        guard: Bool,
+        limit: usize,
+    ) -> Vec<Vec2D<bool>> {
+        let mut found = Vec::new();
+
+        while found.len() < limit {
+            let Some(answer) = self.solve_under(assumptions) else {
+                break;
+            };
+
+            let blocking: Vec<_> =
+                std::iter::zip(self.cells.iter().flatten(), answer.iter().flatten())
+                    .map(|(&lit, &value)| if value { !lit } else { lit })
+                    .chain([!guard])
+                    .collect();
+
+            self.solver.add_clause(blocking);
+            found.push(answer);
+        }
+
+        found
+    }
+
+    fn solve_under(&mut self, assumptions: &[Bool]) -> Option<Vec2D<bool>> {
+        let model = self
+            .solver
+            .solve_under_assumptions(assumptions.iter().copied())
+            .ok()?;
+
+        let answer = self
+            .cells
+            .iter()
+            .map(|row| row.iter().map(|c| model.value(c)).collect())
+            .collect();
+
+        Some(answer)
+    }
+
+    // the index of `line` if it is in the puzzle and `clue` fits it
+    fn check(&self, line: Line, clue: &[u32]) -> Result<usize, String> {
+        let index = self
+            .index(line)
+            .ok_or_else(|| format!("no {line} in the puzzle"))?;
+
+        let len = match line {
+            Line::Row(_) => self.width(),
+            Line::Col(_) => self.height(),
+        };
+
+        if !clue_fits(clue, len) {
+            return Err(format!("clue does not fit in {line}"));
+        }
+
+        Ok(index)
+    }
+
+    fn index(&self, line: Line) -> Option<usize> {
+        match line {
+            Line::Row(row) if row < self.height() => Some(row),
+            Line::Col(col) if col < self.width() => Some(self.height() + col),
+            _ => None,
+        }
+    }
+
+    // returns the activation literal; the clue only holds while it is true
+    fn encode(&mut self, line: Line, clue: &[u32]) -> Bool {
+        let lits: Vec<_> = match line {
+            Line::Row(row) => self.cells[row].clone(),
+            Line::Col(col) => self.cells.iter().map(|row| row[col]).collect(),
+        };
+
+        let active = self.solver.new_lit();
+
+        add_condition(
+            &mut self.solver,
+            &lits,
+            clue,
+            lits.len() as u32,
+            Some(active),
+        );
+
+        active
+    }
+}
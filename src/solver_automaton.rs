@@ -1,46 +1,100 @@
-use kissat::{Solver, Var};
-
-use crate::Vec2D;
-
+#[cfg(any(feature = "kissat", all(feature = "varisat", not(feature = "minisat"))))]
+use crate::logic::Partial;
+use crate::{
+    sat::{self, SolverExt},
+    Vec2D,
+};
+
+#[cfg(feature = "kissat")]
 pub fn solve(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Vec2D<bool>> {
-    let mut s = Solver::new();
-
-    let width = cols.len();
-    let height = rows.len();
-
-    let cells: Vec2D<_> = (0..height)
-        .map(|_| (0..width).map(|_| s.var()).collect())
-        .collect();
+    solve_with::<kissat::Solver>(rows, cols)
+}
 
-    let rows_lits: Vec2D<_> = (0..height)
-        .map(|row| (0..width).map(|col| cells[row][col]).collect())
-        .collect();
+#[cfg(feature = "kissat")]
+pub fn solve_partial(rows: Vec2D<u32>, cols: Vec2D<u32>, known: &Partial) -> Option<Vec2D<bool>> {
+    solve_partial_with::<kissat::Solver>(&rows, &cols, known)
+}
 
-    let cols_lits: Vec2D<_> = (0..width)
-        .map(|col| (0..height).map(|row| cells[row][col]).collect())
-        .collect();
+#[cfg(feature = "kissat")]
+pub fn solutions(rows: Vec2D<u32>, cols: Vec2D<u32>, limit: usize) -> Vec<Vec2D<bool>> {
+    solutions_with::<kissat::Solver>(&rows, &cols, limit)
+}
 
-    for (row, lits) in std::iter::zip(&rows, &rows_lits) {
-        add_condition(&mut s, lits, row, width as u32);
-    }
+pub(crate) fn solve_with<S: SolverExt>(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Vec2D<bool>> {
+    sat::solve_lines::<S>(&rows, &cols, |s, lits, cons, len| {
+        add_condition(s, lits, cons, len, None)
+    })
+}
 
-    for (col, lits) in std::iter::zip(&cols, &cols_lits) {
-        add_condition(&mut s, lits, col, height as u32);
+// the known cells are fixed by unit clauses
+#[cfg(any(feature = "kissat", all(feature = "varisat", not(feature = "minisat"))))]
+pub(crate) fn solve_partial_with<S: SolverExt>(
+    rows: &Vec2D<u32>,
+    cols: &Vec2D<u32>,
+    known: &Partial,
+) -> Option<Vec2D<bool>> {
+    let mut s = S::new();
+    let cells = encode(&mut s, rows, cols);
+
+    for (&lit, &value) in std::iter::zip(cells.iter().flatten(), known.iter().flatten()) {
+        match value {
+            Some(true) => s.add1(lit),
+            Some(false) => s.add1(!lit),
+            None => {}
+        }
     }
 
-    let solution = s.sat()?;
+    sat::model(&mut s, &cells)
+}
 
-    let answer = cells
-        .iter()
-        .map(|row| row.iter().map(|&c| solution.get(c).unwrap()).collect())
-        .collect();
+#[cfg(any(feature = "kissat", all(feature = "varisat", not(feature = "minisat"))))]
+pub(crate) fn solutions_with<S: SolverExt>(
+    rows: &Vec2D<u32>,
+    cols: &Vec2D<u32>,
+    limit: usize,
+) -> Vec<Vec2D<bool>> {
+    sat::solutions(limit, |s: &mut S| encode(s, rows, cols))
+}
 
-    Some(answer)
+#[cfg(any(feature = "kissat", all(feature = "varisat", not(feature = "minisat"))))]
+fn encode<S: SolverExt>(s: &mut S, rows: &Vec2D<u32>, cols: &Vec2D<u32>) -> Vec2D<S::Lit> {
+    sat::encode_lines(s, rows, cols, |s, lits, cons, len| {
+        add_condition(s, lits, cons, len, None)
+    })
 }
 
-fn add_condition(s: &mut Solver, lits: &[Var], cons: &[u32], len: u32) {
+// with a guard, every clause is conditional on it, so the line can be
+// switched off by assuming the guard false
+pub(crate) fn add_condition<S: SolverExt>(
+    s: &mut S,
+    lits: &[S::Lit],
+    cons: &[u32],
+    len: u32,
+    guard: Option<S::Lit>,
+) {
     let len = len as usize;
 
+    let add = |s: &mut S, clause: &[S::Lit]| {
+        let clause: Vec<_> = guard
+            .map(|g| !g)
+            .into_iter()
+            .chain(clause.iter().copied())
+            .collect();
+        s.add(&clause);
+    };
+
+    if len == 0 {
+        return;
+    }
+
+    if cons.is_empty() {
+        for &lit in lits {
+            add(s, &[!lit]);
+        }
+
+        return;
+    }
+
     let num_states = cons.iter().map(|&n| n as usize).sum::<usize>() + cons.len() + 1;
 
     let mut transitions = Vec::new();
@@ -75,29 +129,29 @@ fn add_condition(s: &mut Solver, lits: &[Var], cons: &[u32], len: u32) {
 
             let transition = transitions[state];
 
-            let is = |v: Var, b: bool| if b { v } else { !v };
+            let is = |v: S::Lit, b: bool| if b { v } else { !v };
 
             match transition {
                 (Some(next), Some(same)) => {
                     let looped = states[state][i];
                     let success = states[state + 1][i];
 
-                    s.add3(!curr, !is(cell, next), success);
-                    s.add3(!curr, !is(cell, same), looped);
+                    add(s, &[!curr, !is(cell, next), success]);
+                    add(s, &[!curr, !is(cell, same), looped]);
                 }
 
                 (Some(next), None) => {
                     let success = states[state + 1][i];
 
-                    s.add2(!curr, is(cell, next));
-                    s.add2(!curr, success);
+                    add(s, &[!curr, is(cell, next)]);
+                    add(s, &[!curr, success]);
                 }
 
                 (None, Some(same)) => {
                     let looped = states[state][i];
 
-                    s.add2(!curr, is(cell, same));
-                    s.add2(!curr, looped);
+                    add(s, &[!curr, is(cell, same)]);
+                    add(s, &[!curr, looped]);
                 }
 
                 (None, None) => unreachable!(),
@@ -107,25 +161,26 @@ fn add_condition(s: &mut Solver, lits: &[Var], cons: &[u32], len: u32) {
 
     // end condition
     // only end state and last cell of last block are valid states
-    s.add2(
-        states[num_states - 2][len - 1],
-        states[num_states - 1][len - 1],
+    add(
+        s,
+        &[
+            states[num_states - 2][len - 1],
+            states[num_states - 1][len - 1],
+        ],
     );
 
     // all states except last 2 are not valid end states
     for state in 0..(num_states - 2) {
-        s.add1(!states[state][len - 1]);
+        add(s, &[!states[state][len - 1]]);
     }
 
     // start condition
     // if first cell is picked, go to 2nd state
     // if it is not, remain in the start state
-    s.add2(lits[0], states[0][0]);
-    s.add2(!lits[0], states[1][0]);
+    add(s, &[lits[0], states[0][0]]);
+    add(s, &[!lits[0], states[1][0]]);
 
-    for i in 0..num_states - 2 {
-        for state in i + 2..num_states {
-            s.add1(!states[state][0]);
-        }
+    for state in 2..num_states {
+        add(s, &[!states[state][0]]);
     }
 }
@@ -0,0 +1,144 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{
+    logic::{unwrap, Line, Logic, Partial},
+    Vec2D,
+};
+
+pub fn solve(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Vec2D<bool>> {
+    solutions(rows, cols, 1).pop()
+}
+
+pub fn solve_cancellable(
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    cancel: &AtomicBool,
+) -> Option<Vec2D<bool>> {
+    solutions_cancellable(rows, cols, 1, cancel).pop()
+}
+
+pub fn solve_partial(rows: Vec2D<u32>, cols: Vec2D<u32>, known: &Partial) -> Option<Vec2D<bool>> {
+    let cancel = AtomicBool::new(false);
+    search(rows, cols, known.clone(), 1, &cancel).pop()
+}
+
+pub fn solutions(rows: Vec2D<u32>, cols: Vec2D<u32>, limit: usize) -> Vec<Vec2D<bool>> {
+    solutions_cancellable(rows, cols, limit, &AtomicBool::new(false))
+}
+
+// stops searching once `cancel` is set and returns what was found so far
+pub fn solutions_cancellable(
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    limit: usize,
+    cancel: &AtomicBool,
+) -> Vec<Vec2D<bool>> {
+    let known = vec![vec![None; cols.len()]; rows.len()];
+    search(rows, cols, known, limit, cancel)
+}
+
+fn search(
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    known: Partial,
+    limit: usize,
+    cancel: &AtomicBool,
+) -> Vec<Vec2D<bool>> {
+    let logic = Logic::new(rows, cols);
+
+    let mut search = Search {
+        grid: known,
+        trail: Vec::new(),
+        found: Vec::new(),
+        limit,
+        logic: &logic,
+        cancel,
+    };
+
+    let all: Vec<_> = logic.lines().collect();
+
+    if limit > 0 && search.propagate(all) {
+        search.run();
+    }
+
+    search.found
+}
+
+struct Search<'a> {
+    logic: &'a Logic,
+    grid: Partial,
+    trail: Vec<(usize, usize)>,
+    found: Vec<Vec2D<bool>>,
+    limit: usize,
+    cancel: &'a AtomicBool,
+}
+
+impl Search<'_> {
+    // returns true once enough solutions have been found or the search is
+    // cancelled
+    fn run(&mut self) -> bool {
+        if self.cancel.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        let Some((row, col)) = self.branch() else {
+            self.found.push(unwrap(&self.grid));
+            return self.found.len() >= self.limit;
+        };
+
+        for value in [true, false] {
+            let mark = self.trail.len();
+
+            self.grid[row][col] = Some(value);
+            self.trail.push((row, col));
+
+            if self.propagate([Line::Row(row), Line::Col(col)]) && self.run() {
+                return true;
+            }
+
+            self.undo(mark);
+        }
+
+        false
+    }
+
+    // cells fixed along the way go on the trail even if propagation fails,
+    // so that undo can clear them
+    fn propagate(&mut self, dirty: impl IntoIterator<Item = Line>) -> bool {
+        let mut steps = Vec::new();
+        let result = self.logic.propagate(&mut self.grid, dirty, &mut steps);
+
+        for step in steps {
+            self.trail
+                .extend(step.cells.into_iter().map(|(row, col, _)| (row, col)));
+        }
+
+        result.is_ok()
+    }
+
+    fn undo(&mut self, mark: usize) {
+        for (row, col) in self.trail.drain(mark..) {
+            self.grid[row][col] = None;
+        }
+    }
+
+    // the unknown cell whose row and column have the fewest unknowns left
+    fn branch(&self) -> Option<(usize, usize)> {
+        let unknown =
+            |cells: &mut dyn Iterator<Item = &Option<bool>>| cells.filter(|c| c.is_none()).count();
+
+        let rows: Vec<_> = self
+            .grid
+            .iter()
+            .map(|row| unknown(&mut row.iter()))
+            .collect();
+        let cols: Vec<_> = (0..self.logic.width())
+            .map(|col| unknown(&mut self.grid.iter().map(|row| &row[col])))
+            .collect();
+
+        (0..self.logic.height())
+            .flat_map(|row| (0..self.logic.width()).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.grid[row][col].is_none())
+            .min_by_key(|&(row, col)| rows[row] + cols[col])
+    }
+}
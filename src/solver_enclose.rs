@@ -1,52 +1,29 @@
-use kissat::{Solver, Var};
-
-use crate::Vec2D;
+use crate::{sat::SolverExt, Vec2D};
 
+#[cfg(feature = "kissat")]
 pub fn solve(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Vec2D<bool>> {
-    let mut s = Solver::new();
-
-    let width = cols.len();
-    let height = rows.len();
-
-    let cells: Vec2D<_> = (0..height)
-        .map(|_| (0..width).map(|_| s.var()).collect())
-        .collect();
-
-    let rows_lits: Vec2D<_> = (0..height)
-        .map(|row| (0..width).map(|col| cells[row][col]).collect())
-        .collect();
-
-    let cols_lits: Vec2D<_> = (0..width)
-        .map(|col| (0..height).map(|row| cells[row][col]).collect())
-        .collect();
-
-    for (row, lits) in std::iter::zip(&rows, &rows_lits) {
-        add_condition(&mut s, lits, row, width as u32);
-    }
-
-    for (col, lits) in std::iter::zip(&cols, &cols_lits) {
-        add_condition(&mut s, lits, col, height as u32);
-    }
-
-    let solution = s.sat()?;
-
-    let answer = cells
-        .iter()
-        .map(|row| row.iter().map(|&c| solution.get(c).unwrap()).collect())
-        .collect();
+    solve_with::<kissat::Solver>(rows, cols)
+}
 
-    Some(answer)
+pub(crate) fn solve_with<S: SolverExt>(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Vec2D<bool>> {
+    crate::sat::solve_lines::<S>(&rows, &cols, add_condition)
 }
 
-fn add_condition(s: &mut Solver, lits: &[Var], cons: &[u32], len: u32) {
+// after[j][i] is true if block j starts after cell i, so a cell is enclosed
+// by block j if the block does not start after it but does start after the
+// cell `len` places before it
+fn add_condition<S: SolverExt>(s: &mut S, lits: &[S::Lit], cons: &[u32], len: u32) {
     let num_blocks = cons.len();
     let num_cells = len as usize;
 
-    let after: Vec2D<_> = (0..num_blocks)
-        .map(|_| (0..num_cells).map(|_| s.var()).collect())
-        .collect();
+    let needed = cons.iter().map(|&n| n as usize).sum::<usize>() + num_blocks.saturating_sub(1);
+
+    if needed > num_cells {
+        s.add(&[]);
+        return;
+    }
 
-    let before: Vec2D<_> = (0..num_blocks)
+    let after: Vec2D<_> = (0..num_blocks)
         .map(|_| (0..num_cells).map(|_| s.var()).collect())
         .collect();
 
@@ -54,17 +31,21 @@ fn add_condition(s: &mut Solver, lits: &[Var], cons: &[u32], len: u32) {
         let mut helpers = Vec::new();
 
         for j in 0..num_blocks {
+            let len = cons[j] as usize;
             let helper = s.var();
 
             s.add2(!helper, !after[j][i]);
-            s.add2(!helper, !before[j][i]);
-            s.add3(helper, after[j][i], before[j][i]);
 
-            helpers.push(helper);
-        }
+            if i >= len {
+                s.add2(!helper, after[j][i - len]);
+                s.add(&[helper, after[j][i], !after[j][i - len]]);
+            } else {
+                s.add2(helper, after[j][i]);
+            }
 
-        for &helper in &helpers {
             s.add2(lits[i], !helper);
+
+            helpers.push(helper);
         }
 
         helpers.push(!lits[i]);
@@ -72,28 +53,32 @@ fn add_condition(s: &mut Solver, lits: &[Var], cons: &[u32], len: u32) {
         s.add(&helpers);
     }
 
+    // a block that starts after cell i also starts after cell i - 1
     for i in 1..num_cells {
         for j in 0..num_blocks {
             s.add2(!after[j][i], after[j][i - 1]);
         }
     }
 
-    for i in 0..num_cells - 1 {
-        for j in 0..num_blocks {
-            s.add2(!before[j][i], before[j][i + 1]);
-        }
-    }
-
+    // every block fits before the end of the line
     for j in 0..num_blocks {
         let len = cons[j] as usize;
 
-        for i in 0..num_cells - len {
-            s.add2(after[j][i], before[j][i + len]);
-        }
+        s.add1(!after[j][num_cells - len]);
     }
 
-    for j in 0..num_blocks {
-        s.add1(after[j][0]);
-        s.add1(before[j][num_cells - 1]);
+    // the next block starts at least one cell after the end of this one
+    for j in 1..num_blocks {
+        let len = cons[j - 1] as usize;
+
+        s.add1(after[j][len]);
+
+        for i in 1..num_cells {
+            if i + len < num_cells {
+                s.add2(!after[j - 1][i - 1], after[j][i + len]);
+            } else {
+                s.add1(!after[j - 1][i - 1]);
+            }
+        }
     }
 }
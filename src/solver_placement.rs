@@ -0,0 +1,87 @@
+use crate::{
+    common::{find_solutions, fits},
+    logic::Partial,
+    sat::{self, SolverExt},
+    Vec2D,
+};
+
+pub(crate) fn solve_partial<S: SolverExt>(
+    rows: &Vec2D<u32>,
+    cols: &Vec2D<u32>,
+    known: &Partial,
+) -> Option<Vec2D<bool>> {
+    let mut s = S::new();
+
+    let cells = encode(&mut s, rows, cols, known);
+
+    sat::model(&mut s, &cells)
+}
+
+pub(crate) fn solutions<S: SolverExt>(
+    rows: &Vec2D<u32>,
+    cols: &Vec2D<u32>,
+    limit: usize,
+) -> Vec<Vec2D<bool>> {
+    let known = vec![vec![None; cols.len()]; rows.len()];
+
+    sat::solutions(limit, |s: &mut S| encode(s, rows, cols, &known))
+}
+
+// every line is one of its placements, each placement an and of its cells
+fn encode<S: SolverExt>(
+    s: &mut S,
+    rows: &Vec2D<u32>,
+    cols: &Vec2D<u32>,
+    known: &Partial,
+) -> Vec2D<S::Lit> {
+    let width = cols.len();
+    let height = rows.len();
+
+    let cells = sat::cells(s, width, height);
+
+    for (&lit, &value) in std::iter::zip(cells.iter().flatten(), known.iter().flatten()) {
+        match value {
+            Some(true) => s.add1(lit),
+            Some(false) => s.add1(!lit),
+            None => {}
+        }
+    }
+
+    for ((row, lits), known) in std::iter::zip(rows, &cells).zip(known) {
+        add_condition(s, lits, known, row, width as u32);
+    }
+
+    let cols_lits = sat::columns(&cells, width);
+    let cols_known = sat::columns(known, width);
+
+    for ((col, lits), known) in std::iter::zip(cols, &cols_lits).zip(&cols_known) {
+        add_condition(s, lits, known, col, height as u32);
+    }
+
+    cells
+}
+
+fn add_condition<S: SolverExt>(
+    s: &mut S,
+    lits: &[S::Lit],
+    known: &[Option<bool>],
+    cons: &[u32],
+    len: u32,
+) {
+    let lits: Vec<_> = find_solutions(cons, len)
+        .into_iter()
+        .filter(|solution| fits(solution, known))
+        .map(|solution| {
+            // known cells are already fixed by unit clauses
+            s.and_literal(
+                &std::iter::zip(solution, lits)
+                    .zip(known)
+                    .filter(|(_, k)| k.is_none())
+                    .map(|((c, &lit), _)| if c { lit } else { !lit })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+
+    s.add(&lits);
+}
@@ -0,0 +1,15 @@
+use crate::{
+    logic::{is_solved, presolve, unwrap},
+    solvers::solve_partial,
+    Vec2D,
+};
+
+pub fn solve(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Vec2D<bool>> {
+    let known = presolve(rows.clone(), cols.clone())?;
+
+    if is_solved(&known) {
+        return Some(unwrap(&known));
+    }
+
+    solve_partial(rows, cols, &known)
+}
@@ -1,44 +1,15 @@
-use kissat::{Solver, Var};
-
-use crate::Vec2D;
+use crate::{sat::SolverExt, Vec2D};
 
+#[cfg(feature = "kissat")]
 pub fn solve(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Vec2D<bool>> {
-    let mut s = Solver::new();
-
-    let width = cols.len();
-    let height = rows.len();
-
-    let cells: Vec2D<_> = (0..height)
-        .map(|_| (0..width).map(|_| s.var()).collect())
-        .collect();
-
-    let rows_lits: Vec2D<_> = (0..height)
-        .map(|row| (0..width).map(|col| cells[row][col]).collect())
-        .collect();
-
-    let cols_lits: Vec2D<_> = (0..width)
-        .map(|col| (0..height).map(|row| cells[row][col]).collect())
-        .collect();
-
-    for (row, lits) in std::iter::zip(&rows, &rows_lits) {
-        add_condition(&mut s, lits, row, width as u32);
-    }
-
-    for (col, lits) in std::iter::zip(&cols, &cols_lits) {
-        add_condition(&mut s, lits, col, height as u32);
-    }
-
-    let solution = s.sat()?;
-
-    let answer = cells
-        .iter()
-        .map(|row| row.iter().map(|&c| solution.get(c).unwrap()).collect())
-        .collect();
+    solve_with::<kissat::Solver>(rows, cols)
+}
 
-    Some(answer)
+pub(crate) fn solve_with<S: SolverExt>(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Vec2D<bool>> {
+    crate::sat::solve_lines::<S>(&rows, &cols, add_condition)
 }
 
-fn add_condition(s: &mut Solver, lits: &[Var], cons: &[u32], _len: u32) {
+fn add_condition<S: SolverExt>(s: &mut S, lits: &[S::Lit], cons: &[u32], _len: u32) {
     let num_blocks = cons.len();
     let num_cells = lits.len();
 
@@ -54,15 +25,16 @@ fn add_condition(s: &mut Solver, lits: &[Var], cons: &[u32], _len: u32) {
     for j in 0..num_blocks {
         let len = cons[j] as usize;
 
-        for i in 0..num_cells - len + 1 {
+        for i in 0..num_cells {
+            if i + len > num_cells {
+                s.add1(!starts[j][i]);
+                continue;
+            }
+
             for k in i..i + len {
                 s.add2(!starts[j][i], lits[k]);
             }
         }
-
-        for i in num_cells - len + 1..num_cells {
-            s.add1(!starts[j][i]);
-        }
     }
 
     // Second
@@ -104,13 +76,13 @@ fn add_condition(s: &mut Solver, lits: &[Var], cons: &[u32], _len: u32) {
     // Fourth
     //
     // Blocks must be ordered
-    for j in 0..num_blocks - 1 {
-        let curr = &starts[j];
-        let next = &starts[j + 1];
+    for j in 1..num_blocks {
+        let curr = &starts[j - 1];
+        let next = &starts[j];
 
-        let len = cons[j] as usize;
+        let len = cons[j - 1] as usize;
 
-        for i in 0..num_cells - len - 1 {
+        for i in 0..num_cells {
             let mut clause = Vec::new();
 
             clause.push(!curr[i]);
@@ -0,0 +1,16 @@
+use varisat::Solver;
+
+use crate::{logic::Partial, solver_placement, Vec2D};
+
+pub fn solve(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Vec2D<bool>> {
+    let known = vec![vec![None; cols.len()]; rows.len()];
+    solve_partial(rows, cols, &known)
+}
+
+pub fn solve_partial(rows: Vec2D<u32>, cols: Vec2D<u32>, known: &Partial) -> Option<Vec2D<bool>> {
+    solver_placement::solve_partial::<Solver<'static>>(&rows, &cols, known)
+}
+
+pub fn solutions(rows: Vec2D<u32>, cols: Vec2D<u32>, limit: usize) -> Vec<Vec2D<bool>> {
+    solver_placement::solutions::<Solver<'static>>(&rows, &cols, limit)
+}
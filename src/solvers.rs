@@ -0,0 +1,163 @@
+use std::sync::atomic::AtomicBool;
+
+use crate::{logic::Partial, Vec2D};
+
+#[cfg(feature = "backtrack")]
+use crate::solver_backtrack;
+#[cfg(feature = "kissat")]
+use crate::solver_kissat;
+#[cfg(any(feature = "kissat", feature = "minisat", feature = "varisat"))]
+use crate::solver_probe;
+#[cfg(feature = "varisat")]
+use crate::solver_varisat;
+#[cfg(feature = "minisat")]
+use crate::{session::Session, solver_automaton_minisat, solver_minisat};
+#[cfg(any(feature = "kissat", feature = "varisat"))]
+use crate::{solver_automaton, solver_enclose, solver_starts};
+
+pub type Solve = fn(Vec2D<u32>, Vec2D<u32>) -> Option<Vec2D<bool>>;
+
+pub const SOLVERS: &[(&str, Solve)] = &[
+    #[cfg(feature = "kissat")]
+    ("kissat", solver_kissat::solve),
+    #[cfg(feature = "minisat")]
+    ("minisat", solver_minisat::solve),
+    #[cfg(feature = "kissat")]
+    ("automaton", solver_automaton::solve),
+    #[cfg(feature = "minisat")]
+    ("automaton-minisat", solver_automaton_minisat::solve),
+    #[cfg(feature = "kissat")]
+    ("enclose", solver_enclose::solve),
+    #[cfg(feature = "kissat")]
+    ("starts", solver_starts::solve),
+    #[cfg(feature = "varisat")]
+    ("varisat", solver_varisat::solve),
+    #[cfg(feature = "varisat")]
+    (
+        "automaton-varisat",
+        solver_automaton::solve_with::<varisat::Solver<'static>>,
+    ),
+    #[cfg(feature = "varisat")]
+    (
+        "enclose-varisat",
+        solver_enclose::solve_with::<varisat::Solver<'static>>,
+    ),
+    #[cfg(feature = "varisat")]
+    (
+        "starts-varisat",
+        solver_starts::solve_with::<varisat::Solver<'static>>,
+    ),
+    #[cfg(any(feature = "kissat", feature = "minisat", feature = "varisat"))]
+    ("probe", solver_probe::solve),
+    #[cfg(feature = "backtrack")]
+    ("backtrack", solver_backtrack::solve),
+];
+
+// solvers that give up, with no solution, once the flag is set; the SAT
+// backends have no way to be interrupted
+pub type SolveCancellable = fn(Vec2D<u32>, Vec2D<u32>, &AtomicBool) -> Option<Vec2D<bool>>;
+
+pub const CANCELLABLE: &[(&str, SolveCancellable)] = &[
+    #[cfg(feature = "backtrack")]
+    ("backtrack", solver_backtrack::solve_cancellable),
+];
+
+pub const DEFAULT: &str = SOLVERS[0].0;
+
+pub fn get(name: &str) -> Option<Solve> {
+    SOLVERS
+        .iter()
+        .find(|&&(n, _)| n == name)
+        .map(|&(_, solve)| solve)
+}
+
+pub fn get_cancellable(name: &str) -> Option<SolveCancellable> {
+    CANCELLABLE
+        .iter()
+        .find(|&&(n, _)| n == name)
+        .map(|&(_, solve)| solve)
+}
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    SOLVERS.iter().map(|&(name, _)| name)
+}
+
+pub fn solve(rows: Vec2D<u32>, cols: Vec2D<u32>) -> Option<Vec2D<bool>> {
+    SOLVERS[0].1(rows, cols)
+}
+
+// up to `limit` distinct solutions, on the automaton encoding of the first
+// backend that has one, falling back to the backtracking search
+pub fn solutions(rows: Vec2D<u32>, cols: Vec2D<u32>, limit: usize) -> Vec<Vec2D<bool>> {
+    #[cfg(feature = "minisat")]
+    return Session::new(rows, cols).solutions(limit);
+
+    #[cfg(all(not(feature = "minisat"), feature = "varisat"))]
+    return solver_automaton::solutions_with::<varisat::Solver<'static>>(&rows, &cols, limit);
+
+    #[cfg(all(not(feature = "minisat"), not(feature = "varisat"), feature = "kissat"))]
+    return solver_automaton::solutions(rows, cols, limit);
+
+    #[cfg(all(
+        not(feature = "minisat"),
+        not(feature = "varisat"),
+        not(feature = "kissat")
+    ))]
+    return solver_backtrack::solutions(rows, cols, limit);
+}
+
+// a solution that agrees with the known cells, chosen the same way
+pub fn solve_partial(rows: Vec2D<u32>, cols: Vec2D<u32>, known: &Partial) -> Option<Vec2D<bool>> {
+    #[cfg(feature = "minisat")]
+    return Session::new(rows, cols).solve_partial(known);
+
+    #[cfg(all(not(feature = "minisat"), feature = "varisat"))]
+    return solver_automaton::solve_partial_with::<varisat::Solver<'static>>(&rows, &cols, known);
+
+    #[cfg(all(not(feature = "minisat"), not(feature = "varisat"), feature = "kissat"))]
+    return solver_automaton::solve_partial(rows, cols, known);
+
+    #[cfg(all(
+        not(feature = "minisat"),
+        not(feature = "varisat"),
+        not(feature = "kissat")
+    ))]
+    return solver_backtrack::solve_partial(rows, cols, known);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common;
+
+    fn solves_with_every_backend(name: &str) {
+        let path = format!("{}/nonograms/{name}", env!("CARGO_MANIFEST_DIR"));
+        let (rows, cols) = common::parse(std::fs::read_to_string(path).unwrap()).unwrap();
+
+        for &(solver, solve) in SOLVERS {
+            let solution = solve(rows.clone(), cols.clone())
+                .unwrap_or_else(|| panic!("{solver} found no solution to {name}"));
+
+            assert_eq!(
+                common::clues(&solution),
+                (rows.clone(), cols.clone()),
+                "{solver} solved {name} wrong"
+            );
+        }
+    }
+
+    #[test]
+    fn every_backend_solves_example() {
+        solves_with_every_backend("example");
+    }
+
+    #[test]
+    fn every_backend_solves_hard() {
+        solves_with_every_backend("hard");
+    }
+
+    #[test]
+    fn every_backend_solves_cat2() {
+        solves_with_every_backend("cat2");
+    }
+}
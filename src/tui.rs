@@ -0,0 +1,427 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+use crate::{
+    hint,
+    logic::{format_partial, is_solved, parse_partial, Line, Partial},
+    Vec2D,
+};
+
+const KEYS: &str = "arrows move  space fill  x empty  backspace clear  u undo  r redo  \
+                    h hint  c check  s save  l load  q quit";
+
+#[derive(Clone, Copy)]
+struct Edit {
+    row: usize,
+    col: usize,
+    before: Option<bool>,
+    after: Option<bool>,
+}
+
+struct Game {
+    rows: Vec2D<u32>,
+    cols: Vec2D<u32>,
+    grid: Partial,
+    cursor: (usize, usize),
+    undo: Vec<Vec<Edit>>,
+    redo: Vec<Vec<Edit>>,
+    marked: Vec<(usize, usize)>,
+    mark: Color,
+    message: String,
+    progress: Option<PathBuf>,
+}
+
+// restores the terminal even when the game loop bails out with an error
+struct Terminal;
+
+impl Terminal {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(Terminal)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// plays the puzzle in the terminal; progress is loaded from and saved to
+// `progress` when given
+pub fn play(rows: Vec2D<u32>, cols: Vec2D<u32>, progress: Option<PathBuf>) -> io::Result<()> {
+    let mut game = Game {
+        grid: vec![vec![None; cols.len()]; rows.len()],
+        rows,
+        cols,
+        cursor: (0, 0),
+        undo: Vec::new(),
+        redo: Vec::new(),
+        marked: Vec::new(),
+        mark: Color::Red,
+        message: String::new(),
+        progress,
+    };
+
+    if game.progress.as_ref().is_some_and(|path| path.exists()) {
+        game.load();
+    }
+
+    let _terminal = Terminal::enter()?;
+    let mut out = io::stdout();
+
+    loop {
+        game.draw(&mut out)?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+
+        if key.kind == KeyEventKind::Release {
+            continue;
+        }
+
+        if !game.key(key) {
+            return Ok(());
+        }
+    }
+}
+
+impl Game {
+    fn width(&self) -> usize {
+        self.cols.len()
+    }
+
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    // returns false to quit
+    fn key(&mut self, key: KeyEvent) -> bool {
+        let (row, col) = self.cursor;
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Up => self.cursor.0 = row.saturating_sub(1),
+            KeyCode::Down => self.cursor.0 = (row + 1).min(self.height().saturating_sub(1)),
+            KeyCode::Left => self.cursor.1 = col.saturating_sub(1),
+            KeyCode::Right => self.cursor.1 = (col + 1).min(self.width().saturating_sub(1)),
+            KeyCode::Char(' ') => self.toggle(Some(true)),
+            KeyCode::Char('x') => self.toggle(Some(false)),
+            KeyCode::Backspace | KeyCode::Delete => self.set(vec![(row, col, None)]),
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('r') => self.redo(),
+            KeyCode::Char('h') => self.hint(),
+            KeyCode::Char('c') => self.check(),
+            KeyCode::Char('s') => self.save(),
+            KeyCode::Char('l') => self.load(),
+            _ => {}
+        }
+
+        true
+    }
+
+    fn toggle(&mut self, value: Option<bool>) {
+        let (row, col) = self.cursor;
+
+        if self.width() == 0 || self.height() == 0 {
+            return;
+        }
+
+        let value = if self.grid[row][col] == value {
+            None
+        } else {
+            value
+        };
+
+        self.set(vec![(row, col, value)]);
+    }
+
+    // one undoable action; cells that already hold the value are skipped
+    fn set(&mut self, cells: Vec<(usize, usize, Option<bool>)>) {
+        let edits: Vec<_> = cells
+            .into_iter()
+            .filter(|&(row, col, _)| row < self.height() && col < self.width())
+            .filter(|&(row, col, after)| self.grid[row][col] != after)
+            .map(|(row, col, after)| Edit {
+                row,
+                col,
+                before: self.grid[row][col],
+                after,
+            })
+            .collect();
+
+        if edits.is_empty() {
+            return;
+        }
+
+        for edit in &edits {
+            self.grid[edit.row][edit.col] = edit.after;
+        }
+
+        self.undo.push(edits);
+        self.redo.clear();
+        self.marked.clear();
+        self.message.clear();
+    }
+
+    fn undo(&mut self) {
+        let Some(edits) = self.undo.pop() else {
+            self.message = "nothing to undo".to_string();
+            return;
+        };
+
+        for edit in edits.iter().rev() {
+            self.grid[edit.row][edit.col] = edit.before;
+        }
+
+        self.redo.push(edits);
+        self.marked.clear();
+        self.message.clear();
+    }
+
+    fn redo(&mut self) {
+        let Some(edits) = self.redo.pop() else {
+            self.message = "nothing to redo".to_string();
+            return;
+        };
+
+        for edit in &edits {
+            self.grid[edit.row][edit.col] = edit.after;
+        }
+
+        self.undo.push(edits);
+        self.marked.clear();
+        self.message.clear();
+    }
+
+    fn hint(&mut self) {
+        let Some(hint) = hint::hint(self.rows.clone(), self.cols.clone(), &self.grid) else {
+            self.message = "the puzzle has no solution".to_string();
+            return;
+        };
+
+        // wrong marks come first, deductions from them would be misleading
+        if !hint.mistakes.is_empty() {
+            self.marked = hint.mistakes.iter().map(|c| (c.row, c.col)).collect();
+            self.mark = Color::Red;
+            self.message = format!("{} wrong cells, marked in red", hint.mistakes.len());
+            return;
+        }
+
+        if hint.solved {
+            self.message = "solved!".to_string();
+            return;
+        }
+
+        let Some(deduction) = hint.deduction else {
+            self.message = "no logical deduction, a guess is needed".to_string();
+            return;
+        };
+
+        let cells = deduction
+            .cells
+            .iter()
+            .map(|c| (c.row, c.col, Some(c.filled)))
+            .collect();
+
+        self.set(cells);
+        self.marked = deduction.cells.iter().map(|c| (c.row, c.col)).collect();
+        self.mark = Color::Yellow;
+        self.message = deduction.to_string();
+    }
+
+    // marks are only wrong if no solution agrees with them, so a puzzle with
+    // several solutions accepts any of them
+    fn check(&mut self) {
+        let Some(mistakes) = hint::mistakes(self.rows.clone(), self.cols.clone(), &self.grid)
+        else {
+            self.message = "the puzzle has no solution".to_string();
+            return;
+        };
+
+        self.mark = Color::Red;
+        self.marked = mistakes.iter().map(|c| (c.row, c.col)).collect();
+
+        let left = self.grid.iter().flatten().filter(|c| c.is_none()).count();
+
+        self.message = match (self.marked.len(), left) {
+            (0, 0) => "solved!".to_string(),
+            (0, left) => format!("no mistakes so far, {left} cells left"),
+            (wrong, _) => format!("{wrong} wrong cells, marked in red"),
+        };
+    }
+
+    fn save(&mut self) {
+        let Some(path) = &self.progress else {
+            self.message = "no progress file, start with --progress PATH".to_string();
+            return;
+        };
+
+        self.message = match fs::write(path, format_partial(&self.grid)) {
+            Ok(()) => format!("saved to {}", path.display()),
+            Err(e) => format!("error writing {}: {e}", path.display()),
+        };
+    }
+
+    fn load(&mut self) {
+        let Some(path) = &self.progress else {
+            self.message = "no progress file, start with --progress PATH".to_string();
+            return;
+        };
+
+        let grid = match fs::read_to_string(path) {
+            Ok(input) => parse_partial(&input),
+            Err(e) => {
+                self.message = format!("error reading {}: {e}", path.display());
+                return;
+            }
+        };
+
+        let (width, height) = (self.width(), self.height());
+
+        match grid {
+            Some(grid) if grid.len() == height && grid.iter().all(|row| row.len() == width) => {
+                self.message = format!("loaded {}", path.display());
+                self.grid = grid;
+                self.undo.clear();
+                self.redo.clear();
+                self.marked.clear();
+            }
+            _ => self.message = format!("{} does not match the puzzle", path.display()),
+        }
+    }
+
+    // the filled runs, unknown cells counting as empty, equal the clue
+    fn matches(&self, line: Line) -> bool {
+        let (cells, clue): (Vec<_>, _) = match line {
+            Line::Row(row) => (self.grid[row].clone(), &self.rows[row]),
+            Line::Col(col) => (
+                self.grid.iter().map(|row| row[col]).collect(),
+                &self.cols[col],
+            ),
+        };
+
+        let runs: Vec<u32> = cells
+            .split(|&c| c != Some(true))
+            .filter(|run| !run.is_empty())
+            .map(|run| run.len() as u32)
+            .collect();
+
+        runs == *clue
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let clue = |nums: &[u32]| {
+            let nums: Vec<_> = nums.iter().map(u32::to_string).collect();
+            nums.join(" ")
+        };
+
+        let top = self.cols.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        let left = self.rows.iter().map(|r| clue(r).len()).max().unwrap_or(0);
+
+        queue!(out, Clear(ClearType::All))?;
+
+        for (col, nums) in self.cols.iter().enumerate() {
+            let color = if self.matches(Line::Col(col)) {
+                Color::Green
+            } else {
+                Color::Reset
+            };
+
+            queue!(out, SetForegroundColor(color))?;
+
+            // bottom-aligned above the column
+            for (i, n) in nums.iter().enumerate() {
+                let y = (top - nums.len() + i) as u16;
+                let x = (left + 1 + col * 2) as u16;
+                queue!(out, MoveTo(x, y), Print(format!("{n:>2}")))?;
+            }
+        }
+
+        for (row, nums) in self.rows.iter().enumerate() {
+            let y = (top + row) as u16;
+
+            let color = if self.matches(Line::Row(row)) {
+                Color::Green
+            } else {
+                Color::Reset
+            };
+
+            queue!(
+                out,
+                MoveTo(0, y),
+                SetForegroundColor(color),
+                Print(format!("{:>left$}", clue(nums))),
+                ResetColor,
+                Print(" "),
+            )?;
+
+            for (col, cell) in self.grid[row].iter().enumerate() {
+                let glyph = match cell {
+                    Some(true) => "██",
+                    Some(false) => " ×",
+                    None => " ·",
+                };
+
+                if self.marked.contains(&(row, col)) {
+                    queue!(out, SetForegroundColor(self.mark))?;
+                }
+
+                if self.cursor == (row, col) {
+                    queue!(out, SetAttribute(Attribute::Reverse))?;
+                }
+
+                queue!(
+                    out,
+                    Print(glyph),
+                    SetAttribute(Attribute::Reset),
+                    ResetColor
+                )?;
+            }
+        }
+
+        let solved = is_solved(&self.grid) && self.lines_match();
+
+        let status = if solved && self.message.is_empty() {
+            "all clues satisfied"
+        } else {
+            &self.message
+        };
+
+        let y = (top + self.height() + 1) as u16;
+
+        queue!(
+            out,
+            MoveTo(0, y),
+            Print(status),
+            MoveTo(0, y + 1),
+            SetAttribute(Attribute::Dim),
+            Print(KEYS),
+            SetAttribute(Attribute::Reset),
+        )?;
+
+        out.flush()
+    }
+
+    fn lines_match(&self) -> bool {
+        (0..self.height())
+            .map(Line::Row)
+            .chain((0..self.width()).map(Line::Col))
+            .all(|line| self.matches(line))
+    }
+}
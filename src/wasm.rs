@@ -0,0 +1,89 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    common::{self, Puzzle},
+    hint,
+    logic::Partial,
+    report::Report,
+    solvers, Vec2D,
+};
+
+fn puzzle(json: &str) -> Result<(Vec2D<u32>, Vec2D<u32>), JsError> {
+    let (rows, cols) = common::parse_json(json.to_string())
+        .ok_or_else(|| JsError::new("error parsing nonogram"))?;
+
+    common::validate(&rows, &cols)
+        .map_err(|e| JsError::new(&format!("incorrect nonogram: {e}")))?;
+
+    Ok((rows, cols))
+}
+
+/// Parses the text format into puzzle JSON.
+#[wasm_bindgen]
+pub fn parse(text: &str) -> Result<String, JsError> {
+    let (rows, cols) =
+        common::parse(text.to_string()).ok_or_else(|| JsError::new("error parsing nonogram"))?;
+
+    Ok(serde_json::to_string(&Puzzle { rows, cols })?)
+}
+
+#[wasm_bindgen]
+pub fn validate(puzzle_json: &str) -> Result<(), JsError> {
+    puzzle(puzzle_json).map(|_| ())
+}
+
+/// Solves a puzzle and returns a report as JSON. Uses the default solver
+/// when `solver` is not given.
+#[wasm_bindgen]
+pub fn solve(puzzle_json: &str, solver: Option<String>) -> Result<String, JsError> {
+    let (rows, cols) = puzzle(puzzle_json)?;
+
+    let name = solver.as_deref().unwrap_or(solvers::DEFAULT);
+    let solve = solvers::get(name).ok_or_else(|| JsError::new("wrong solver name"))?;
+
+    let (width, height) = (cols.len(), rows.len());
+
+    Ok(Report::new(name, width, height, solve(rows, cols)).to_json())
+}
+
+/// Solves a puzzle and returns its cells row by row, 1 for filled and 0 for
+/// empty, or an empty array if it has no solution.
+#[wasm_bindgen]
+pub fn solve_cells(puzzle_json: &str) -> Result<Vec<u8>, JsError> {
+    let (rows, cols) = puzzle(puzzle_json)?;
+
+    let cells = solvers::solve(rows, cols)
+        .map(|solution| solution.into_iter().flatten().map(u8::from).collect())
+        .unwrap_or_default();
+
+    Ok(cells)
+}
+
+#[wasm_bindgen]
+pub fn is_unique(puzzle_json: &str) -> Result<bool, JsError> {
+    let (rows, cols) = puzzle(puzzle_json)?;
+
+    Ok(solvers::solutions(rows, cols, 2).len() == 1)
+}
+
+/// Takes the player's grid row by row, 1 for filled, 0 for empty and -1 for
+/// unknown, and returns the hint as JSON.
+#[wasm_bindgen]
+pub fn hint(puzzle_json: &str, grid: &[i8]) -> Result<String, JsError> {
+    let (rows, cols) = puzzle(puzzle_json)?;
+
+    let width = cols.len();
+
+    if grid.len() != width * rows.len() {
+        return Err(JsError::new("grid does not match the puzzle size"));
+    }
+
+    let grid: Partial = grid
+        .chunks(width.max(1))
+        .map(|row| row.iter().map(|&c| (c >= 0).then_some(c > 0)).collect())
+        .collect();
+
+    let hint = hint::hint(rows, cols, &grid).ok_or_else(|| JsError::new("puzzle is unsolvable"))?;
+
+    Ok(serde_json::to_string(&hint)?)
+}